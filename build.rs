@@ -1,6 +1,12 @@
+#[cfg(feature = "capi")]
+extern crate cbindgen;
+
 use std::env;
 
 fn main() {
+    #[cfg(feature = "capi")]
+    generate_header();
+
     let package_name = "postal";
     let shared_libs = ["postal"];
 
@@ -16,3 +22,21 @@ fn main() {
     }
     println!("cargo:rustc-link-lib=dylib={}", package_name);
 }
+
+// Writes the header of the C API (`src/capi.rs`) into `$OUT_DIR/rpostal.h`, for the tests to check
+// that `include/rpostal.h` is up to date. The installed header is generated by cargo-c.
+#[cfg(feature = "capi")]
+fn generate_header() {
+    use std::path::PathBuf;
+
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("invalid cbindgen.toml");
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("failed to generate the C header")
+        .write_to_file(out_dir.join("rpostal.h"));
+}
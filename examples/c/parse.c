@@ -0,0 +1,41 @@
+/* Build with cargo-c (https://github.com/lu-zero/cargo-c):
+ *   cargo cbuild --release --features capi
+ *   cc examples/c/parse.c -Iinclude -Ltarget/x86_64-unknown-linux-gnu/release -lrpostal -o parse
+ */
+#include <stdio.h>
+
+#include "rpostal.h"
+
+int main(int argc, char **argv) {
+    const char *address = argc > 1 ? argv[1] : "781 Franklin Ave Crown Heights Brooklyn NY 11216";
+
+    if (rpostal_capi_version() >> 16 != RPOSTAL_CAPI_VERSION_MAJOR) {
+        fprintf(stderr, "incompatible rpostal library\n");
+        return 1;
+    }
+    RpostalHandle *handle = rpostal_new(NULL);
+    if (handle == NULL) {
+        fprintf(stderr, "%s\n", rpostal_last_error());
+        return 1;
+    }
+
+    /* The last error is reset by each call, so it must be checked right away. */
+    char *parsed = rpostal_parse(handle, address, "{\"country\": \"us\"}");
+    if (parsed == NULL) {
+        fprintf(stderr, "parse: %s\n", rpostal_last_error());
+    } else {
+        printf("parsed: %s\n", parsed);
+        rpostal_string_free(parsed);
+    }
+
+    char *expansions = rpostal_expand(handle, address, NULL);
+    if (expansions == NULL) {
+        fprintf(stderr, "expand: %s\n", rpostal_last_error());
+    } else {
+        printf("expansions: %s\n", expansions);
+        rpostal_string_free(expansions);
+    }
+
+    rpostal_free(handle);
+    return 0;
+}
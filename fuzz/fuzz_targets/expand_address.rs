@@ -0,0 +1,18 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use rpostal::NormalizeOptions;
+use rpostal_fuzz::{core, normalize_options};
+
+#[derive(Arbitrary, Debug)]
+struct Input<'a> {
+    address: &'a [u8],
+    #[arbitrary(with = normalize_options)]
+    options: NormalizeOptions,
+}
+
+fuzz_target!(|data: Input<'_>| {
+    // Fails (without calling libpostal) if the address contains a NUL byte.
+    let _ = core().expand_address_bytes(data.address, data.options);
+});
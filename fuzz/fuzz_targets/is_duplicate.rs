@@ -0,0 +1,75 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use rpostal::{DuplicateOptions, FuzzyDuplicateOptions};
+use rpostal_fuzz::{addresses, classifier, input, languages, Component};
+
+#[derive(Arbitrary, Debug)]
+enum Input<'a> {
+    Name(&'a [u8], &'a [u8]),
+    Street(&'a [u8], &'a [u8]),
+    HouseNumber(&'a [u8], &'a [u8]),
+    PoBox(&'a [u8], &'a [u8]),
+    Unit(&'a [u8], &'a [u8]),
+    Floor(&'a [u8], &'a [u8]),
+    PostalCode(&'a [u8], &'a [u8]),
+    Toponym(Vec<Component<'a>>, Vec<Component<'a>>),
+    NameFuzzy(Vec<(&'a [u8], f64)>, Vec<(&'a [u8], f64)>, f64, f64),
+    StreetFuzzy(Vec<(&'a [u8], f64)>, Vec<(&'a [u8], f64)>, f64, f64),
+}
+
+fn tokens(tokens: &[(&[u8], f64)]) -> Option<Vec<(String, f64)>> {
+    tokens
+        .iter()
+        .map(|&(token, score)| Some((input(token)?, score)))
+        .collect()
+}
+
+fuzz_target!(|data: (Input<'_>, Vec<u8>)| {
+    let (data, language_indices) = data;
+    let classifier = classifier();
+    let options = DuplicateOptions {
+        languages: languages(&language_indices),
+    };
+
+    macro_rules! check {
+        ($method:ident, $a:expr, $b:expr) => {{
+            // Fails (without calling libpostal) if a value contains a NUL byte.
+            let _ = classifier.$method($a, $b, &options);
+        }};
+    }
+    macro_rules! check_fuzzy {
+        ($method:ident, $a:expr, $b:expr, $needs_review:expr, $likely_dupe:expr) => {
+            if let (Some(a), Some(b)) = (tokens(&$a), tokens(&$b)) {
+                let options = FuzzyDuplicateOptions {
+                    languages: options.languages.clone(),
+                    needs_review_threshold: $needs_review,
+                    likely_dupe_threshold: $likely_dupe,
+                };
+                classifier.$method(&a, &b, &options);
+            }
+        };
+    }
+
+    match data {
+        Input::Name(a, b) => check!(is_name_duplicate_bytes, a, b),
+        Input::Street(a, b) => check!(is_street_duplicate_bytes, a, b),
+        Input::HouseNumber(a, b) => check!(is_house_number_duplicate_bytes, a, b),
+        Input::PoBox(a, b) => check!(is_po_box_duplicate_bytes, a, b),
+        Input::Unit(a, b) => check!(is_unit_duplicate_bytes, a, b),
+        Input::Floor(a, b) => check!(is_floor_duplicate_bytes, a, b),
+        Input::PostalCode(a, b) => check!(is_postal_code_duplicate_bytes, a, b),
+        Input::Toponym(a, b) => {
+            if let (Some(a), Some(b)) = (addresses(&a), addresses(&b)) {
+                classifier.is_toponym_duplicate(&a, &b, &options);
+            }
+        }
+        Input::NameFuzzy(a, b, needs_review, likely_dupe) => {
+            check_fuzzy!(is_name_duplicate_fuzzy, a, b, needs_review, likely_dupe)
+        }
+        Input::StreetFuzzy(a, b, needs_review, likely_dupe) => {
+            check_fuzzy!(is_street_duplicate_fuzzy, a, b, needs_review, likely_dupe)
+        }
+    }
+});
@@ -0,0 +1,39 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use rpostal::{NearDupeHashOptions, NearDupeRecord};
+use rpostal_fuzz::{addresses, classifier, languages, near_dupe_hash_options, Component};
+
+#[derive(Arbitrary, Debug)]
+struct Input<'a> {
+    components: Vec<Component<'a>>,
+    languages: Vec<u8>,
+    latlon: Option<(f64, f64)>,
+    #[arbitrary(with = near_dupe_hash_options)]
+    options: NearDupeHashOptions,
+}
+
+fuzz_target!(|data: Input<'_>| {
+    let addresses = match addresses(&data.components) {
+        Some(a) => a,
+        None => return,
+    };
+    let languages = languages(&data.languages);
+    let options = data.options;
+    let classifier = classifier();
+
+    let mut record = NearDupeRecord::new(&addresses).languages(&languages);
+    if let Some((latitude, longitude)) = data.latlon {
+        record = record.latlon(latitude, longitude);
+    }
+    // Invalid coordinates and geohash precisions are rejected before reaching libpostal.
+    let _ = classifier.near_dupe_hashes_for(&record, &options);
+
+    let options = NearDupeHashOptions {
+        with_latlon: false,
+        ..options
+    };
+    classifier.near_dupe_hashes(&addresses, &options);
+    classifier.near_dupe_hashes_languages(&addresses, &options, &languages);
+});
@@ -0,0 +1,53 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use rpostal::AddressParserOptions;
+use rpostal_fuzz::{input, parser};
+
+#[derive(Arbitrary, Debug)]
+struct Input<'a> {
+    address: &'a [u8],
+    language: Option<&'a [u8]>,
+    country: Option<&'a [u8]>,
+}
+
+fuzz_target!(|data: Input<'_>| {
+    let mut options = AddressParserOptions::new();
+    match (data.language.map(input), data.country.map(input)) {
+        (Some(None), _) | (_, Some(None)) => return,
+        (language, country) => {
+            options
+                .set_language(language.flatten())
+                .set_country(country.flatten());
+        }
+    }
+
+    let parser = parser();
+    let parsed = match parser.parse_address_bytes(data.address, &options) {
+        Ok(parsed) => parsed,
+        // NUL bytes can't be given to libpostal.
+        Err(_) => return,
+    };
+    let address = match std::str::from_utf8(data.address) {
+        Ok(address) => address,
+        Err(_) => return,
+    };
+    // The `&str` APIs go through the same libpostal call, copying the components as is in
+    // strict mode and borrowing them (as empty strings if they aren't valid UTF-8) in
+    // `parse_address_response`.
+    if let Ok(strict) = parser.parse_address_strict(address, &options) {
+        assert_eq!(strict, parsed);
+    }
+    match (parsed, parser.parse_address_response(address, &options)) {
+        (Some(parsed), Some(response)) => {
+            assert_eq!(parsed.len(), response.len());
+            for (address, (label, value)) in parsed.iter().zip(response.iter()) {
+                assert_eq!(address.label.to_str().unwrap_or(""), label);
+                assert_eq!(address.value.to_str().unwrap_or(""), value);
+            }
+        }
+        (None, None) => {}
+        _ => panic!("parse_address_bytes and parse_address_response disagree"),
+    }
+});
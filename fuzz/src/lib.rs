@@ -0,0 +1,133 @@
+//! Helpers shared by the fuzz targets of `fuzz_targets/`. Run them with
+//! `cargo fuzz run <target>` from this directory.
+//!
+//! The libpostal models are loaded once per process, from the directory given in the
+//! `LIBPOSTAL_DATA_DIR` environment variable if set.
+
+use std::env;
+use std::ffi::CString;
+
+use arbitrary::{Arbitrary, Unstructured};
+use once_cell::sync::OnceCell;
+use rpostal::{
+    Address, AddressComponents, AddressParser, Core, Language, LanguageClassifier,
+    NearDupeHashOptions, NormalizeOptions,
+};
+
+static CORE: OnceCell<Core> = OnceCell::new();
+static PARSER: OnceCell<AddressParser<'static>> = OnceCell::new();
+static CLASSIFIER: OnceCell<LanguageClassifier<'static>> = OnceCell::new();
+
+pub fn core() -> &'static Core {
+    CORE.get_or_init(|| {
+        match env::var_os("LIBPOSTAL_DATA_DIR") {
+            Some(dir) => Core::setup_datadir(dir),
+            None => Core::setup(),
+        }
+        .expect("failed to setup libpostal")
+    })
+}
+
+pub fn parser() -> &'static AddressParser<'static> {
+    let core = core();
+    PARSER.get_or_init(|| {
+        match env::var_os("LIBPOSTAL_DATA_DIR") {
+            Some(dir) => core.setup_parser_datadir(dir),
+            None => core.setup_parser(),
+        }
+        .expect("failed to setup the libpostal address parser")
+    })
+}
+
+pub fn classifier() -> &'static LanguageClassifier<'static> {
+    let core = core();
+    CLASSIFIER.get_or_init(|| {
+        match env::var_os("LIBPOSTAL_DATA_DIR") {
+            Some(dir) => core.setup_language_classifier_datadir(dir),
+            None => core.setup_language_classifier(),
+        }
+        .expect("failed to setup the libpostal language classifier")
+    })
+}
+
+/// Converts fuzzer bytes into a string for the APIs which only take strings: invalid UTF-8 is
+/// replaced and inputs with a NUL byte (which can't be given to libpostal) are skipped.
+pub fn input(data: &[u8]) -> Option<String> {
+    if data.contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(data).into_owned())
+}
+
+/// Picks languages among `Language::all()`.
+pub fn languages(indices: &[u8]) -> Vec<Language> {
+    let all = Language::all();
+    indices
+        .iter()
+        .map(|&i| all[i as usize % all.len()].clone())
+        .collect()
+}
+
+/// Label and value of a parsed component.
+#[derive(Arbitrary, Debug)]
+pub struct Component<'a> {
+    pub label: &'a [u8],
+    pub value: &'a [u8],
+}
+
+/// Converts fuzzed components into `Address`es, or returns `None` if one of them contains a
+/// NUL byte.
+pub fn addresses(components: &[Component<'_>]) -> Option<Vec<Address>> {
+    components
+        .iter()
+        .map(|c| {
+            Some(Address {
+                label: CString::new(c.label).ok()?,
+                value: CString::new(c.value).ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Builds random `NormalizeOptions`, for `#[arbitrary(with = normalize_options)]`.
+pub fn normalize_options(u: &mut Unstructured<'_>) -> arbitrary::Result<NormalizeOptions> {
+    Ok(NormalizeOptions {
+        languages: languages(u.arbitrary()?),
+        address_components: AddressComponents::from_bits(u.arbitrary()?),
+        latin_ascii: u.arbitrary()?,
+        transliterate: u.arbitrary()?,
+        strip_accents: u.arbitrary()?,
+        decompose: u.arbitrary()?,
+        lowercase: u.arbitrary()?,
+        trim_string: u.arbitrary()?,
+        drop_parentheticals: u.arbitrary()?,
+        replace_numeric_hyphens: u.arbitrary()?,
+        delete_numeric_hyphens: u.arbitrary()?,
+        split_alpha_from_numeric: u.arbitrary()?,
+        replace_word_hyphens: u.arbitrary()?,
+        delete_word_hyphens: u.arbitrary()?,
+        delete_final_periods: u.arbitrary()?,
+        delete_acronym_periods: u.arbitrary()?,
+        drop_english_possessives: u.arbitrary()?,
+        delete_apostrophes: u.arbitrary()?,
+        expand_numex: u.arbitrary()?,
+        roman_numerals: u.arbitrary()?,
+    })
+}
+
+/// Builds random `NearDupeHashOptions`, for `#[arbitrary(with = near_dupe_hash_options)]`.
+pub fn near_dupe_hash_options(u: &mut Unstructured<'_>) -> arbitrary::Result<NearDupeHashOptions> {
+    Ok(NearDupeHashOptions {
+        with_name: u.arbitrary()?,
+        with_address: u.arbitrary()?,
+        with_unit: u.arbitrary()?,
+        with_city_or_equivalent: u.arbitrary()?,
+        with_small_containing_boundaries: u.arbitrary()?,
+        with_postal_code: u.arbitrary()?,
+        with_latlon: u.arbitrary()?,
+        geohash_precision: u.arbitrary()?,
+        name_and_address_keys: u.arbitrary()?,
+        name_only_keys: u.arbitrary()?,
+        address_only_keys: u.arbitrary()?,
+    })
+}
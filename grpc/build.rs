@@ -0,0 +1,9 @@
+// `protox` compiles the protobuf files so `protoc` doesn't need to be installed.
+fn main() {
+    let descriptors = protox::compile(["postal/v1/postal.proto"], ["proto"])
+        .expect("failed to compile protobuf files");
+    tonic_build::configure()
+        .compile_fds(descriptors)
+        .expect("failed to generate gRPC code");
+    println!("cargo:rerun-if-changed=proto");
+}
@@ -0,0 +1,50 @@
+//! Parses the addresses given as arguments with a running `rpostal-grpc` server.
+//!
+//! ```text
+//! cargo run --example client -- "30 W 26th St, New York" "10 Downing St, London"
+//! ```
+
+use std::env;
+
+use rpostal_grpc::proto::postal_client::PostalClient;
+use rpostal_grpc::proto::{ExpandRequest, ParseRequest};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let endpoint = env::var("RPOSTAL_GRPC").unwrap_or_else(|_| "http://127.0.0.1:50051".to_owned());
+    let mut client = PostalClient::connect(endpoint).await?;
+
+    let requests = env::args()
+        .skip(1)
+        .enumerate()
+        .map(|(id, address)| ParseRequest {
+            id: id.to_string(),
+            address,
+            ..ParseRequest::default()
+        })
+        .collect::<Vec<_>>();
+    let first = requests.first().map(|r| r.address.clone());
+
+    let mut responses = client
+        .parse_stream(tokio_stream::iter(requests))
+        .await?
+        .into_inner();
+    while let Some(response) = responses.message().await? {
+        println!("#{}", response.id);
+        for component in response.components {
+            println!("    {}: {}", component.label, component.value);
+        }
+    }
+
+    if let Some(address) = first {
+        let expansions = client
+            .expand(ExpandRequest {
+                address,
+                ..ExpandRequest::default()
+            })
+            .await?
+            .into_inner();
+        println!("expansions: {:?}", expansions.expansions);
+    }
+    Ok(())
+}
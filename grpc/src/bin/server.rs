@@ -0,0 +1,40 @@
+//! Runs the `Postal` gRPC service.
+//!
+//! ```text
+//! rpostal-grpc [--listen 127.0.0.1:50051] [--data-dir /usr/local/share/libpostal]
+//! ```
+
+use std::env;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process;
+
+use rpostal_grpc::{PostalServer, PostalService};
+
+fn usage() -> ! {
+    eprintln!("usage: rpostal-grpc [--listen ADDRESS] [--data-dir DIRECTORY]");
+    process::exit(1);
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut listen: SocketAddr = "127.0.0.1:50051".parse()?;
+    let mut data_dir = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--listen" => listen = args.next().unwrap_or_else(|| usage()).parse()?,
+            "--data-dir" => data_dir = Some(PathBuf::from(args.next().unwrap_or_else(|| usage()))),
+            _ => usage(),
+        }
+    }
+
+    let service = PostalService::setup(data_dir.as_deref()).ok_or("failed to setup libpostal")?;
+    eprintln!("listening on {}", listen);
+    tonic::transport::Server::builder()
+        .add_service(PostalServer::new(service))
+        .serve(listen)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,395 @@
+//! gRPC service exposing the libpostal parser, expander and deduplication functions. See
+//! `proto/postal/v1/postal.proto` for the service definition.
+
+// `tonic::Status` is the error type imposed by the generated service trait.
+#![allow(clippy::result_large_err)]
+
+use std::ffi::CString;
+use std::path::Path;
+use std::pin::Pin;
+
+use rpostal::{
+    Address, AddressComponents, AddressParser, AddressParserOptions, Core, DuplicateOptions,
+    DuplicateStatus, Language, LanguageClassifier, NearDupeHashOptions, NearDupeRecord,
+    NormalizeOptions, ParsedAddress, UnknownLanguage,
+};
+use tokio::sync::mpsc;
+use tokio::task;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status, Streaming};
+
+pub mod proto {
+    tonic::include_proto!("postal.v1");
+}
+
+use proto::postal_server::Postal;
+pub use proto::postal_server::PostalServer;
+
+/// Implementation of the `Postal` service.
+///
+/// The libpostal calls are blocking so they're run with `tokio::task::spawn_blocking`.
+#[derive(Clone, Copy)]
+pub struct PostalService {
+    core: &'static Core,
+    parser: &'static AddressParser<'static>,
+    classifier: &'static LanguageClassifier<'static>,
+}
+
+impl PostalService {
+    pub fn new(
+        core: &'static Core,
+        parser: &'static AddressParser<'static>,
+        classifier: &'static LanguageClassifier<'static>,
+    ) -> PostalService {
+        PostalService {
+            core,
+            parser,
+            classifier,
+        }
+    }
+
+    /// Loads the libpostal models (from `data_dir` if given) for the whole process lifetime.
+    pub fn setup(data_dir: Option<&Path>) -> Option<PostalService> {
+        let core: &'static Core = Box::leak(Box::new(match data_dir {
+            Some(dir) => Core::setup_datadir(dir)?,
+            None => Core::setup()?,
+        }));
+        let parser = Box::leak(Box::new(match data_dir {
+            Some(dir) => core.setup_parser_datadir(dir)?,
+            None => core.setup_parser()?,
+        }));
+        let classifier = Box::leak(Box::new(match data_dir {
+            Some(dir) => core.setup_language_classifier_datadir(dir)?,
+            None => core.setup_language_classifier()?,
+        }));
+        Some(PostalService::new(core, parser, classifier))
+    }
+
+    fn parse(&self, request: proto::ParseRequest) -> Result<proto::ParseResponse, Status> {
+        if request.address.contains('\0') {
+            return Err(Status::invalid_argument("unexpected NUL in address"));
+        }
+        let mut options = AddressParserOptions::new();
+        options
+            .set_language(Some(request.language).filter(|l| !l.is_empty()))
+            .set_country(Some(request.country).filter(|c| !c.is_empty()));
+        let components = self
+            .parser
+            .parse_address(&request.address, &options)
+            .ok_or_else(|| Status::internal("libpostal failed to parse the address"))?
+            .into_iter()
+            .map(|a| proto::Component {
+                label: a.label.to_string_lossy().into_owned(),
+                value: a.value.to_string_lossy().into_owned(),
+            })
+            .collect();
+        Ok(proto::ParseResponse {
+            id: request.id,
+            components,
+        })
+    }
+
+    fn compare(
+        &self,
+        address1: &[Address],
+        address2: &[Address],
+        options: &DuplicateOptions,
+    ) -> Vec<proto::ComponentComparison> {
+        let classifier = self.classifier;
+        let parsed1 = ParsedAddress::from_addresses(address1);
+        let parsed2 = ParsedAddress::from_addresses(address2);
+        let checks: &[(&str, &str, DuplicateCheck)] = &[
+            ("name", "house", LanguageClassifier::is_name_duplicate),
+            (
+                "house_number",
+                "house_number",
+                LanguageClassifier::is_house_number_duplicate,
+            ),
+            ("street", "road", LanguageClassifier::is_street_duplicate),
+            ("unit", "unit", LanguageClassifier::is_unit_duplicate),
+            ("floor", "level", LanguageClassifier::is_floor_duplicate),
+            ("po_box", "po_box", LanguageClassifier::is_po_box_duplicate),
+            (
+                "postal_code",
+                "postcode",
+                LanguageClassifier::is_postal_code_duplicate,
+            ),
+        ];
+        let mut comparisons = Vec::new();
+
+        for &(component, label, check) in checks {
+            if let (Some(a), Some(b)) = (parsed1.get(label), parsed2.get(label)) {
+                comparisons.push(comparison(component, check(classifier, a, b, options)));
+            }
+        }
+        if has_toponym(address1) && has_toponym(address2) {
+            comparisons.push(comparison(
+                "toponym",
+                classifier.is_toponym_duplicate(address1, address2, options),
+            ));
+        }
+        comparisons
+    }
+}
+
+type DuplicateCheck =
+    fn(&LanguageClassifier<'static>, &str, &str, &DuplicateOptions) -> DuplicateStatus;
+
+fn has_toponym(addresses: &[Address]) -> bool {
+    addresses.iter().any(|a| {
+        rpostal::component_for_label(&a.label.to_string_lossy())
+            == Some(rpostal::AddressComponent::Toponym)
+    })
+}
+
+fn comparison(component: &str, status: DuplicateStatus) -> proto::ComponentComparison {
+    let status = match status {
+        DuplicateStatus::Null => proto::DuplicateStatus::Null,
+        DuplicateStatus::NonDuplicate => proto::DuplicateStatus::NonDuplicate,
+        DuplicateStatus::PossibleDuplicateNeedsReview => {
+            proto::DuplicateStatus::PossibleDuplicateNeedsReview
+        }
+        DuplicateStatus::LikelyDuplicate => proto::DuplicateStatus::LikelyDuplicate,
+        DuplicateStatus::ExactDuplicate => proto::DuplicateStatus::ExactDuplicate,
+    };
+    proto::ComponentComparison {
+        component: component.to_owned(),
+        status: status as i32,
+    }
+}
+
+fn to_addresses(components: Vec<proto::Component>) -> Result<Vec<Address>, Status> {
+    components
+        .into_iter()
+        .map(|c| {
+            Ok(Address {
+                label: CString::new(c.label)
+                    .map_err(|_| Status::invalid_argument("unexpected NUL in label"))?,
+                value: CString::new(c.value)
+                    .map_err(|_| Status::invalid_argument("unexpected NUL in value"))?,
+            })
+        })
+        .collect()
+}
+
+fn to_languages(languages: Vec<String>) -> Result<Vec<Language>, Status> {
+    languages
+        .iter()
+        .map(|l| {
+            l.parse()
+                .map_err(|e: UnknownLanguage| Status::invalid_argument(e.to_string()))
+        })
+        .collect()
+}
+
+fn normalize_options(request: &proto::ExpandRequest) -> Result<NormalizeOptions, Status> {
+    let address_components = if request.address_components.is_empty() {
+        AddressComponents::all()
+    } else {
+        request
+            .address_components
+            .parse::<AddressComponents>()
+            .map_err(|e| Status::invalid_argument(e.to_string()))?
+    };
+    Ok(NormalizeOptions {
+        languages: to_languages(request.languages.clone())?,
+        address_components,
+        ..NormalizeOptions::default()
+    })
+}
+
+fn near_dupe_hash_options(options: Option<proto::NearDupeHashOptions>) -> NearDupeHashOptions {
+    let mut ret = NearDupeHashOptions::default();
+    let options = match options {
+        Some(o) => o,
+        None => return ret,
+    };
+    macro_rules! set {
+        ($($field:ident),*) => {
+            $(if let Some(value) = options.$field {
+                ret.$field = value;
+            })*
+        };
+    }
+    set!(
+        with_name,
+        with_address,
+        with_unit,
+        with_city_or_equivalent,
+        with_small_containing_boundaries,
+        with_postal_code,
+        geohash_precision,
+        name_and_address_keys,
+        name_only_keys,
+        address_only_keys
+    );
+    ret
+}
+
+async fn blocking<T, F>(f: F) -> Result<T, Status>
+where
+    F: FnOnce() -> Result<T, Status> + Send + 'static,
+    T: Send + 'static,
+{
+    task::spawn_blocking(f)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+}
+
+#[tonic::async_trait]
+impl Postal for PostalService {
+    async fn parse(
+        &self,
+        request: Request<proto::ParseRequest>,
+    ) -> Result<Response<proto::ParseResponse>, Status> {
+        let service = *self;
+        let request = request.into_inner();
+        blocking(move || service.parse(request))
+            .await
+            .map(Response::new)
+    }
+
+    type ParseStreamStream =
+        Pin<Box<dyn Stream<Item = Result<proto::ParseResponse, Status>> + Send + 'static>>;
+
+    async fn parse_stream(
+        &self,
+        request: Request<Streaming<proto::ParseRequest>>,
+    ) -> Result<Response<Self::ParseStreamStream>, Status> {
+        let service = *self;
+        let mut inbound = request.into_inner();
+        let (sender, receiver) = mpsc::channel(64);
+
+        tokio::spawn(async move {
+            while let Some(request) = inbound.next().await {
+                let response = match request {
+                    Ok(request) => blocking(move || service.parse(request)).await,
+                    Err(status) => Err(status),
+                };
+                let failed = response.is_err();
+                // The client went away.
+                if sender.send(response).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(receiver))))
+    }
+
+    async fn expand(
+        &self,
+        request: Request<proto::ExpandRequest>,
+    ) -> Result<Response<proto::ExpandResponse>, Status> {
+        let core = self.core;
+        let request = request.into_inner();
+        blocking(move || {
+            let options = normalize_options(&request)?;
+            Ok(proto::ExpandResponse {
+                expansions: core.expand_address(&request.address, options),
+            })
+        })
+        .await
+        .map(Response::new)
+    }
+
+    async fn expand_root(
+        &self,
+        request: Request<proto::ExpandRequest>,
+    ) -> Result<Response<proto::ExpandResponse>, Status> {
+        let core = self.core;
+        let request = request.into_inner();
+        blocking(move || {
+            let options = normalize_options(&request)?;
+            Ok(proto::ExpandResponse {
+                expansions: core.expand_address_root(&request.address, options),
+            })
+        })
+        .await
+        .map(Response::new)
+    }
+
+    async fn near_dupe_hashes(
+        &self,
+        request: Request<proto::NearDupeHashesRequest>,
+    ) -> Result<Response<proto::NearDupeHashesResponse>, Status> {
+        let classifier = self.classifier;
+        let request = request.into_inner();
+        blocking(move || {
+            let addresses = to_addresses(request.components)?;
+            let languages = to_languages(request.languages)?;
+            let mut options = near_dupe_hash_options(request.options);
+            let mut record = NearDupeRecord::new(&addresses).languages(&languages);
+            if let Some(latlon) = request.latlon {
+                options.with_latlon = true;
+                record = record.latlon(latlon.latitude, latlon.longitude);
+            }
+            let hashes = classifier
+                .near_dupe_hashes_for(&record, &options)
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+            Ok(proto::NearDupeHashesResponse { hashes })
+        })
+        .await
+        .map(Response::new)
+    }
+
+    async fn compare_addresses(
+        &self,
+        request: Request<proto::CompareAddressesRequest>,
+    ) -> Result<Response<proto::CompareAddressesResponse>, Status> {
+        let service = *self;
+        let request = request.into_inner();
+        blocking(move || {
+            let address1 = to_addresses(request.address1)?;
+            let address2 = to_addresses(request.address2)?;
+            let options = DuplicateOptions {
+                languages: to_languages(request.languages)?,
+            };
+            Ok(proto::CompareAddressesResponse {
+                comparisons: service.compare(&address1, &address2, &options),
+            })
+        })
+        .await
+        .map(Response::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rpostal::AddressComponent;
+    use tonic::Code;
+
+    fn expand_request(languages: &[&str], address_components: &str) -> proto::ExpandRequest {
+        proto::ExpandRequest {
+            address: "30 W 26th St".to_owned(),
+            languages: languages.iter().map(|l| l.to_string()).collect(),
+            address_components: address_components.to_owned(),
+        }
+    }
+
+    #[test]
+    fn empty_address_components_means_all() {
+        let options = normalize_options(&expand_request(&[], "")).unwrap();
+        assert_eq!(options.address_components, AddressComponents::all());
+        assert!(options.languages.is_empty());
+    }
+
+    #[test]
+    fn address_components_are_parsed() {
+        let options = normalize_options(&expand_request(&["en", "fr"], "street|toponym")).unwrap();
+        assert_eq!(
+            options.address_components,
+            AddressComponent::Street | AddressComponent::Toponym
+        );
+        assert_eq!(options.languages, [Language::English, Language::French]);
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        let error = normalize_options(&expand_request(&[], "street|road")).unwrap_err();
+        assert_eq!(error.code(), Code::InvalidArgument);
+        let error = normalize_options(&expand_request(&["xx"], "")).unwrap_err();
+        assert_eq!(error.code(), Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,185 @@
+//! Runs the service on a local port and calls it with the generated client. Needs the libpostal
+//! models to be installed.
+
+use std::net::SocketAddr;
+use std::sync::OnceLock;
+
+use rpostal_grpc::proto::postal_client::PostalClient;
+use rpostal_grpc::proto::{
+    CompareAddressesRequest, Component, DuplicateStatus, ExpandRequest, ParseRequest,
+};
+use rpostal_grpc::{PostalServer, PostalService};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::StreamExt;
+use tonic::transport::{Channel, Server};
+use tonic::Code;
+
+fn service() -> PostalService {
+    static SERVICE: OnceLock<PostalService> = OnceLock::new();
+    *SERVICE.get_or_init(|| PostalService::setup(None).expect("failed to setup libpostal"))
+}
+
+async fn client() -> PostalClient<Channel> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address: SocketAddr = listener.local_addr().unwrap();
+    tokio::spawn(
+        Server::builder()
+            .add_service(PostalServer::new(service()))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    PostalClient::connect(format!("http://{}", address))
+        .await
+        .unwrap()
+}
+
+fn parse_request(id: &str, address: &str) -> ParseRequest {
+    ParseRequest {
+        id: id.to_owned(),
+        address: address.to_owned(),
+        ..ParseRequest::default()
+    }
+}
+
+fn expand_request(languages: &[&str]) -> ExpandRequest {
+    ExpandRequest {
+        address: "30 W 26th St".to_owned(),
+        languages: languages.iter().map(|l| l.to_string()).collect(),
+        address_components: String::new(),
+    }
+}
+
+fn component(label: &str, value: &str) -> Component {
+    Component {
+        label: label.to_owned(),
+        value: value.to_owned(),
+    }
+}
+
+#[tokio::test]
+async fn parse() {
+    let mut client = client().await;
+
+    let response = client
+        .parse(ParseRequest {
+            country: "us".to_owned(),
+            ..parse_request("1", "781 Franklin Ave Crown Heights Brooklyn NY 11216")
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.id, "1");
+    assert!(response
+        .components
+        .contains(&component("road", "franklin ave")));
+
+    let status = client
+        .parse(parse_request("2", "30 W 26th St\0"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn parse_stream() {
+    let mut client = client().await;
+    let requests = vec![
+        parse_request("a", "30 W 26th St, New York"),
+        parse_request("b", "10 Downing St, London"),
+        parse_request("c", "Rue de Rivoli, Paris"),
+    ];
+
+    let responses = client
+        .parse_stream(tokio_stream::iter(requests))
+        .await
+        .unwrap()
+        .into_inner()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .unwrap();
+    assert_eq!(
+        responses.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(),
+        ["a", "b", "c"]
+    );
+    assert!(responses.iter().all(|r| !r.components.is_empty()));
+}
+
+#[tokio::test]
+async fn parse_stream_stops_at_the_first_error() {
+    let mut client = client().await;
+    let requests = vec![
+        parse_request("a", "30 W 26th St\0"),
+        parse_request("b", "10 Downing St, London"),
+    ];
+
+    let mut responses = client
+        .parse_stream(tokio_stream::iter(requests))
+        .await
+        .unwrap()
+        .into_inner();
+    let status = responses.next().await.unwrap().unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert!(responses.next().await.is_none());
+}
+
+#[tokio::test]
+async fn expand() {
+    let mut client = client().await;
+
+    let response = client
+        .expand(expand_request(&["en"]))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(response
+        .expansions
+        .contains(&"30 west 26th street".to_owned()));
+
+    let status = client.expand(expand_request(&["xx"])).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn compare_addresses() {
+    let mut client = client().await;
+
+    let response = client
+        .compare_addresses(CompareAddressesRequest {
+            address1: vec![
+                component("house_number", "30"),
+                component("road", "w 26th st"),
+                component("city", "new york"),
+            ],
+            address2: vec![
+                component("house_number", "30"),
+                component("road", "west 26th street"),
+                component("city", "new york"),
+            ],
+            languages: vec!["en".to_owned()],
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let status = |name: &str| {
+        response
+            .comparisons
+            .iter()
+            .find(|c| c.component == name)
+            .map(|c| c.status())
+    };
+    assert_eq!(
+        status("house_number"),
+        Some(DuplicateStatus::ExactDuplicate)
+    );
+    assert_eq!(status("street"), Some(DuplicateStatus::ExactDuplicate));
+    assert_eq!(status("unit"), None);
+
+    let status = client
+        .compare_addresses(CompareAddressesRequest {
+            address1: vec![component("road\0", "w 26th st")],
+            ..CompareAddressesRequest::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
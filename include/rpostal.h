@@ -0,0 +1,193 @@
+/* Generated by cbindgen from the `capi` feature of the rpostal crate. Do not edit. */
+
+#ifndef RPOSTAL_H
+#define RPOSTAL_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Major version of the C API. Incremented on incompatible changes.
+ */
+#define RPOSTAL_CAPI_VERSION_MAJOR 1
+
+/**
+ * Minor version of the C API. Incremented when functions are added.
+ */
+#define RPOSTAL_CAPI_VERSION_MINOR 0
+
+/**
+ * Number of parsed addresses cached by a new handle.
+ */
+#define RPOSTAL_DEFAULT_CACHE_CAPACITY 4096
+
+/**
+ * Loaded libpostal models. Created with `rpostal_new` and freed with `rpostal_free`.
+ *
+ * A handle can be used from several threads at once.
+ */
+typedef struct RpostalHandle RpostalHandle;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Returns the version of the C API as `major << 16 | minor`, to check it against the
+ * `RPOSTAL_CAPI_VERSION_*` constants of the header the program was compiled with.
+ */
+uint32_t rpostal_capi_version(void);
+
+/**
+ * Loads the libpostal models from `data_dir`, or from the default directory if it's `NULL`.
+ * Returns `NULL` on failure.
+ *
+ * # Safety
+ *
+ * `data_dir` must be `NULL` or a valid NUL-terminated string.
+ */
+struct RpostalHandle *rpostal_new(const char *data_dir);
+
+/**
+ * Frees a handle returned by `rpostal_new`. Does nothing if `handle` is `NULL`.
+ *
+ * # Safety
+ *
+ * `handle` must be `NULL` or a handle returned by `rpostal_new` which wasn't freed yet and
+ * isn't used by another thread.
+ */
+void rpostal_free(struct RpostalHandle *handle);
+
+/**
+ * Sets how many parsed addresses `handle` caches, `0` disabling the cache. The cache is
+ * emptied. Returns `false` if `handle` is `NULL`.
+ *
+ * # Safety
+ *
+ * `handle` must be `NULL` or a valid handle.
+ */
+bool rpostal_set_cache_capacity(const struct RpostalHandle *handle, size_t capacity);
+
+/**
+ * Frees a string returned by this library. Does nothing if `s` is `NULL`.
+ *
+ * # Safety
+ *
+ * `s` must be `NULL` or a string returned by this library which wasn't freed yet.
+ */
+void rpostal_string_free(char *s);
+
+/**
+ * Returns the error of the last call of the current thread which returned `NULL`, or `NULL`
+ * if it succeeded. The string is owned by the library and valid until the next call.
+ */
+const char *rpostal_last_error(void);
+
+/**
+ * Parses `address`. `options` is `NULL` or `{"language": "en", "country": "us"}`.
+ *
+ * Returns `{"components": [{"label": ..., "value": ...}], "quality": {"score": ...,
+ * "warnings": [{"code": ..., "message": ...}]}}`.
+ *
+ * # Safety
+ *
+ * `handle` must be a valid handle, `address` a valid NUL-terminated string and `options`
+ * `NULL` or a valid NUL-terminated string.
+ */
+char *rpostal_parse(const struct RpostalHandle *handle, const char *address, const char *options);
+
+/**
+ * Expands `address` into its normalized variants. `options` is `NULL` or a
+ * `NormalizeOptions` object. Returns a JSON array of strings.
+ *
+ * # Safety
+ *
+ * `handle` must be a valid handle, `address` a valid NUL-terminated string and `options`
+ * `NULL` or a valid NUL-terminated string.
+ */
+char *rpostal_expand(const struct RpostalHandle *handle, const char *address, const char *options);
+
+/**
+ * Same as `rpostal_expand` but only returns the root forms of the address.
+ *
+ * # Safety
+ *
+ * Same as `rpostal_expand`.
+ */
+char *rpostal_expand_root(const struct RpostalHandle *handle,
+                          const char *address,
+                          const char *options);
+
+/**
+ * Computes the near-dupe hashes of a parsed address. `components` is a
+ * `[{"label": ..., "value": ...}]` array (like the `components` returned by `rpostal_parse`)
+ * and `options` is `NULL` or a `NearDupeHashOptions` object, which can also have the `latitude`
+ * and `longitude` of the address for `with_latlon`. Returns a JSON array of strings.
+ *
+ * # Safety
+ *
+ * `handle` must be a valid handle, `components` a valid NUL-terminated string and `options`
+ * `NULL` or a valid NUL-terminated string.
+ */
+char *rpostal_near_dupe_hashes(const struct RpostalHandle *handle,
+                               const char *components,
+                               const char *options);
+
+/**
+ * Compares two values of `component` (`name`, `street`, `house_number`, `po_box`, `unit`,
+ * `floor`, `postal_code` or `toponym`, for which the values are whole addresses parsed
+ * first). `options` is `NULL` or `{"languages": ["en"]}`.
+ *
+ * Returns the status name as a JSON string, for example `"likely_duplicate"`.
+ *
+ * # Safety
+ *
+ * `handle` must be a valid handle, `component`, `value1` and `value2` valid NUL-terminated
+ * strings and `options` `NULL` or a valid NUL-terminated string.
+ */
+char *rpostal_is_duplicate(const struct RpostalHandle *handle,
+                           const char *component,
+                           const char *value1,
+                           const char *value2,
+                           const char *options);
+
+/**
+ * Parses both addresses and compares each component present in both of them.
+ *
+ * Returns an object mapping each compared component to its status name, for example
+ * `{"house_number": "exact_duplicate", "street": "likely_duplicate"}`.
+ *
+ * # Safety
+ *
+ * `handle` must be a valid handle, `address1` and `address2` valid NUL-terminated strings and
+ * `options` `NULL` or a valid NUL-terminated string.
+ */
+char *rpostal_compare(const struct RpostalHandle *handle,
+                      const char *address1,
+                      const char *address2,
+                      const char *options);
+
+#if defined(RPOSTAL_FORMAT)
+/**
+ * Formats a parsed address as a postal label using the address conventions of `country` (an
+ * ISO 3166-1 alpha-2 code, or `NULL` for the default template). `components` is a
+ * `[{"label": ..., "value": ...}]` array. Returns the label as a JSON string.
+ *
+ * # Safety
+ *
+ * `handle` must be a valid handle, `components` a valid NUL-terminated string and `country`
+ * `NULL` or a valid NUL-terminated string.
+ */
+char *rpostal_format(const struct RpostalHandle *handle,
+                     const char *components,
+                     const char *country);
+#endif
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RPOSTAL_H */
@@ -0,0 +1,45 @@
+"""Polars expressions parsing and expanding addresses with libpostal."""
+
+from __future__ import annotations
+
+from pathlib import Path
+
+import polars as pl
+from polars.plugins import register_plugin_function
+
+from rpostal_polars._internal import __version__
+
+__all__ = ["parse_address", "expand_address", "__version__"]
+
+_LIB = Path(__file__).parent
+
+
+def parse_address(
+    expr: pl.Expr | str,
+    *,
+    language: str | None = None,
+    country: str | None = None,
+) -> pl.Expr:
+    """Parses each address into a struct with one string field per libpostal label."""
+    return register_plugin_function(
+        plugin_path=_LIB,
+        function_name="parse_address",
+        args=expr,
+        kwargs={"language": language, "country": country},
+        is_elementwise=True,
+    )
+
+
+def expand_address(expr: pl.Expr | str, *, options: dict | None = None) -> pl.Expr:
+    """Returns the list of the normalized variants of each address.
+
+    `options` is the serialized form of `rpostal::NormalizeOptions`: missing fields use the
+    libpostal defaults.
+    """
+    return register_plugin_function(
+        plugin_path=_LIB,
+        function_name="expand_address",
+        args=expr,
+        kwargs={"options": options or {}},
+        is_elementwise=True,
+    )
@@ -0,0 +1,149 @@
+//! Polars expression plugins parsing and expanding whole string columns with libpostal.
+//!
+//! The libpostal data is loaded on first use, from the directory given in the
+//! `LIBPOSTAL_DATA_DIR` environment variable if set.
+//!
+//! The columns are handed to `rpostal::arrow` through the Arrow C data interface since polars
+//! has its own Arrow implementation.
+
+use std::env;
+use std::mem;
+
+use arrow_array::ffi::{self as arrow_ffi, FFI_ArrowArray, FFI_ArrowSchema};
+use arrow_array::{Array as _, LargeStringArray};
+use once_cell::sync::Lazy;
+use polars::prelude::*;
+use polars_arrow::ffi::{ArrowArray, ArrowSchema};
+use pyo3::prelude::*;
+use pyo3_polars::derive::polars_expr;
+use rpostal::{AddressParser, AddressParserOptions, Core, NormalizeOptions, LABELS};
+use serde::Deserialize;
+
+static CORE: Lazy<Core> = Lazy::new(|| {
+    match env::var_os("LIBPOSTAL_DATA_DIR") {
+        Some(dir) => Core::setup_datadir(dir),
+        None => Core::setup(),
+    }
+    .expect("failed to setup libpostal")
+});
+
+static PARSER: Lazy<AddressParser<'static>> = Lazy::new(|| {
+    match env::var_os("LIBPOSTAL_DATA_DIR") {
+        Some(dir) => CORE.setup_parser_datadir(dir),
+        None => CORE.setup_parser(),
+    }
+    .expect("failed to setup the libpostal address parser")
+});
+
+#[derive(Deserialize)]
+struct ParseKwargs {
+    language: Option<String>,
+    country: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ExpandKwargs {
+    #[serde(default)]
+    options: NormalizeOptions,
+}
+
+fn parsed_address_output(input_fields: &[Field]) -> PolarsResult<Field> {
+    let fields = LABELS
+        .iter()
+        .map(|label| Field::new((*label).into(), DataType::String))
+        .collect();
+    Ok(Field::new(
+        input_fields[0].name().clone(),
+        DataType::Struct(fields),
+    ))
+}
+
+fn expansions_output(input_fields: &[Field]) -> PolarsResult<Field> {
+    Ok(Field::new(
+        input_fields[0].name().clone(),
+        DataType::List(Box::new(DataType::String)),
+    ))
+}
+
+// Calls `f` on each chunk of the `addresses` string column and gathers the arrays it returns
+// into a series.
+fn map_chunks<F>(addresses: &Series, mut f: F) -> PolarsResult<Series>
+where
+    F: FnMut(&LargeStringArray) -> arrow_array::ArrayRef,
+{
+    let addresses = addresses.cast(&DataType::String)?;
+    let chunks = (0..addresses.n_chunks())
+        .map(|i| {
+            let chunk = from_polars(addresses.to_arrow(i, CompatLevel::oldest()))?;
+            let chunk = chunk
+                .as_any()
+                .downcast_ref::<LargeStringArray>()
+                .ok_or_else(|| polars_err!(ComputeError: "expected a large string array"))?;
+            to_polars(f(chunk).as_ref())
+        })
+        .collect::<PolarsResult<Vec<_>>>()?;
+    Series::try_from((addresses.name().clone(), chunks))
+}
+
+fn from_polars(array: Box<dyn polars_arrow::array::Array>) -> PolarsResult<arrow_array::ArrayRef> {
+    let field = polars_arrow::datatypes::Field::new("".into(), array.dtype().clone(), true);
+    let schema = polars_arrow::ffi::export_field_to_c(&field);
+    let array = polars_arrow::ffi::export_array_to_c(array);
+    // Both sides are the structs of the Arrow C data interface.
+    let (array, schema) = unsafe {
+        (
+            mem::transmute::<ArrowArray, FFI_ArrowArray>(array),
+            mem::transmute::<ArrowSchema, FFI_ArrowSchema>(schema),
+        )
+    };
+    let data = unsafe { arrow_ffi::from_ffi(array, &schema) }
+        .map_err(|e| polars_err!(ComputeError: "{}", e))?;
+    Ok(arrow_array::make_array(data))
+}
+
+fn to_polars(array: &dyn arrow_array::Array) -> PolarsResult<Box<dyn polars_arrow::array::Array>> {
+    let (array, schema) =
+        arrow_ffi::to_ffi(&array.to_data()).map_err(|e| polars_err!(ComputeError: "{}", e))?;
+    let (array, schema) = unsafe {
+        (
+            mem::transmute::<FFI_ArrowArray, ArrowArray>(array),
+            mem::transmute::<FFI_ArrowSchema, ArrowSchema>(schema),
+        )
+    };
+    let field = unsafe { polars_arrow::ffi::import_field_from_c(&schema) }?;
+    unsafe { polars_arrow::ffi::import_array_from_c(array, field.dtype) }
+}
+
+/// Same as `rpostal::arrow::parse_addresses`: one string field per label, null or unparsable
+/// addresses giving null rows.
+#[polars_expr(output_type_func=parsed_address_output)]
+fn parse_address(inputs: &[Series], kwargs: ParseKwargs) -> PolarsResult<Series> {
+    let mut options = AddressParserOptions::new();
+    options
+        .set_language(kwargs.language)
+        .set_country(kwargs.country);
+
+    map_chunks(&inputs[0], |addresses| {
+        Arc::new(rpostal::arrow::parse_addresses(
+            &PARSER, addresses, &options,
+        ))
+    })
+}
+
+/// Same as `rpostal::arrow::expand_addresses`: null addresses give null lists.
+#[polars_expr(output_type_func=expansions_output)]
+fn expand_address(inputs: &[Series], kwargs: ExpandKwargs) -> PolarsResult<Series> {
+    map_chunks(&inputs[0], |addresses| {
+        Arc::new(rpostal::arrow::expand_addresses(
+            &CORE,
+            addresses,
+            kwargs.options.clone(),
+        ))
+    })
+}
+
+#[pymodule]
+fn _internal(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
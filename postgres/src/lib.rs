@@ -0,0 +1,192 @@
+//! Postgres extension exposing the libpostal parser and expander.
+//!
+//! ```sql
+//! CREATE EXTENSION rpostal_pg;
+//! SELECT postal_parse('30 W 26th St, New York');
+//! SELECT postal_expand('30 W 26th St');
+//! SELECT postal_near_dupe_hashes(postal_parse('30 W 26th St, New York'));
+//! ```
+//!
+//! The models are loaded once per backend process, on first use, from the `postal.data_dir`
+//! setting if set or from the libpostal default directory otherwise.
+
+use std::ffi::CString;
+
+use once_cell::sync::OnceCell;
+use pgrx::guc::{GucContext, GucFlags, GucRegistry, GucSetting};
+use pgrx::prelude::*;
+use pgrx::JsonB;
+use rpostal::{
+    Address, AddressParser, AddressParserOptions, Core, LanguageClassifier, ParsedAddress,
+};
+use serde_json::{Map, Value};
+
+::pgrx::pg_module_magic!();
+
+static DATA_DIR: GucSetting<Option<CString>> = GucSetting::<Option<CString>>::new(None);
+
+static CORE: OnceCell<Core> = OnceCell::new();
+static PARSER: OnceCell<AddressParser<'static>> = OnceCell::new();
+static CLASSIFIER: OnceCell<LanguageClassifier<'static>> = OnceCell::new();
+
+#[pg_guard]
+pub extern "C-unwind" fn _PG_init() {
+    GucRegistry::define_string_guc(
+        c"postal.data_dir",
+        c"libpostal data directory.",
+        c"Directory containing the libpostal models. The default directory is used if empty.",
+        &DATA_DIR,
+        GucContext::Suset,
+        GucFlags::default(),
+    );
+}
+
+fn data_dir() -> Option<String> {
+    DATA_DIR
+        .get()
+        .map(|d| d.to_string_lossy().into_owned())
+        .filter(|d| !d.is_empty())
+}
+
+fn core() -> &'static Core {
+    CORE.get_or_init(|| {
+        match data_dir() {
+            Some(dir) => Core::setup_datadir(dir),
+            None => Core::setup(),
+        }
+        .unwrap_or_else(|| error!("failed to setup libpostal"))
+    })
+}
+
+fn parser() -> &'static AddressParser<'static> {
+    PARSER.get_or_init(|| {
+        match data_dir() {
+            Some(dir) => core().setup_parser_datadir(dir),
+            None => core().setup_parser(),
+        }
+        .unwrap_or_else(|| error!("failed to setup the libpostal address parser"))
+    })
+}
+
+fn classifier() -> &'static LanguageClassifier<'static> {
+    CLASSIFIER.get_or_init(|| {
+        match data_dir() {
+            Some(dir) => core().setup_language_classifier_datadir(dir),
+            None => core().setup_language_classifier(),
+        }
+        .unwrap_or_else(|| error!("failed to setup the libpostal language classifier"))
+    })
+}
+
+/// Returns an object mapping each label to its value. If a label appears more than once, the
+/// values are joined with a space.
+#[pg_extern(immutable, parallel_safe, strict)]
+fn postal_parse(address: &str) -> Option<JsonB> {
+    let parsed = parser().parse_address(address, &AddressParserOptions::new())?;
+    let object = ParsedAddress::from_addresses(&parsed)
+        .iter()
+        .map(|(label, value)| (label.to_owned(), Value::from(value)))
+        .collect::<Map<_, _>>();
+    Some(JsonB(Value::Object(object)))
+}
+
+#[pg_extern(immutable, parallel_safe, strict)]
+fn postal_expand(address: &str) -> Vec<String> {
+    let core = core();
+    core.expand_address(address, core.get_default_options())
+}
+
+/// Takes an object mapping labels to values, like the ones returned by `postal_parse`.
+#[pg_extern(immutable, parallel_safe, strict)]
+fn postal_near_dupe_hashes(components: JsonB) -> Vec<String> {
+    let object = match components.0 {
+        Value::Object(o) => o,
+        other => error!("expected a jsonb object, got `{}`", other),
+    };
+    let addresses = object
+        .into_iter()
+        .filter_map(|(label, value)| {
+            let value = match value {
+                Value::String(s) => s,
+                Value::Null => return None,
+                other => other.to_string(),
+            };
+            Some(Address {
+                label: CString::new(label).unwrap_or_else(|_| error!("unexpected NUL in label")),
+                value: CString::new(value).unwrap_or_else(|_| error!("unexpected NUL in value")),
+            })
+        })
+        .collect::<Vec<_>>();
+    let classifier = classifier();
+    classifier.near_dupe_hashes(&addresses, &classifier.get_near_dupe_hash_default_options())
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+    use pgrx::prelude::*;
+    use pgrx::JsonB;
+    use serde_json::json;
+
+    const ADDRESS: &str = "781 Franklin Ave Crown Heights Brooklyn NY 11216";
+
+    #[pg_test]
+    fn test_postal_parse() {
+        let parsed = crate::postal_parse(ADDRESS).expect("parse failed").0;
+        assert_eq!(parsed["house_number"], "781");
+        assert_eq!(parsed["road"], "franklin ave");
+        assert_eq!(parsed["postcode"], "11216");
+    }
+
+    #[pg_test]
+    fn test_postal_parse_sql() {
+        let road =
+            Spi::get_one::<String>(&format!("SELECT postal_parse('{}') ->> 'road'", ADDRESS));
+        assert_eq!(road, Ok(Some("franklin ave".to_owned())));
+        let null = Spi::get_one::<JsonB>("SELECT postal_parse(NULL)");
+        assert!(matches!(null, Ok(None)));
+    }
+
+    #[pg_test]
+    fn test_postal_expand() {
+        let expansions = Spi::get_one::<Vec<String>>("SELECT postal_expand('30 W 26th St')")
+            .unwrap()
+            .unwrap();
+        assert!(expansions.iter().any(|e| e == "30 west 26th street"));
+    }
+
+    #[pg_test]
+    fn test_postal_near_dupe_hashes() {
+        let hashes = Spi::get_one::<Vec<String>>(&format!(
+            "SELECT postal_near_dupe_hashes(postal_parse('{}'))",
+            ADDRESS
+        ))
+        .unwrap()
+        .unwrap();
+        assert!(!hashes.is_empty());
+
+        let with_null = crate::postal_near_dupe_hashes(JsonB(json!({
+            "house_number": "781",
+            "road": "franklin ave",
+            "unit": null,
+            "city": "brooklyn",
+        })));
+        assert!(!with_null.is_empty());
+    }
+
+    #[pg_test(error = "expected a jsonb object, got `[1]`")]
+    fn test_postal_near_dupe_hashes_not_an_object() {
+        crate::postal_near_dupe_hashes(JsonB(json!([1])));
+    }
+}
+
+/// Required by `cargo pgrx test`.
+#[cfg(test)]
+pub mod pg_test {
+    pub fn setup(_options: Vec<&str>) {}
+
+    #[must_use]
+    pub fn postgresql_conf_options() -> Vec<&'static str> {
+        vec![]
+    }
+}
@@ -0,0 +1,142 @@
+from typing import Optional
+
+class NormalizeOptions:
+    languages: list[str]
+    address_components: str
+    latin_ascii: bool
+    transliterate: bool
+    strip_accents: bool
+    decompose: bool
+    lowercase: bool
+    trim_string: bool
+    drop_parentheticals: bool
+    replace_numeric_hyphens: bool
+    delete_numeric_hyphens: bool
+    split_alpha_from_numeric: bool
+    replace_word_hyphens: bool
+    delete_word_hyphens: bool
+    delete_final_periods: bool
+    delete_acronym_periods: bool
+    drop_english_possessives: bool
+    delete_apostrophes: bool
+    expand_numex: bool
+    roman_numerals: bool
+    def __init__(
+        self,
+        *,
+        languages: list[str] = ...,
+        address_components: str = ...,
+        latin_ascii: bool = ...,
+        transliterate: bool = ...,
+        strip_accents: bool = ...,
+        decompose: bool = ...,
+        lowercase: bool = ...,
+        trim_string: bool = ...,
+        drop_parentheticals: bool = ...,
+        replace_numeric_hyphens: bool = ...,
+        delete_numeric_hyphens: bool = ...,
+        split_alpha_from_numeric: bool = ...,
+        replace_word_hyphens: bool = ...,
+        delete_word_hyphens: bool = ...,
+        delete_final_periods: bool = ...,
+        delete_acronym_periods: bool = ...,
+        drop_english_possessives: bool = ...,
+        delete_apostrophes: bool = ...,
+        expand_numex: bool = ...,
+        roman_numerals: bool = ...,
+    ) -> None: ...
+
+class NearDupeHashOptions:
+    with_name: bool
+    with_address: bool
+    with_unit: bool
+    with_city_or_equivalent: bool
+    with_small_containing_boundaries: bool
+    with_postal_code: bool
+    with_latlon: bool
+    geohash_precision: int
+    name_and_address_keys: bool
+    name_only_keys: bool
+    address_only_keys: bool
+    def __init__(
+        self,
+        *,
+        with_name: bool = ...,
+        with_address: bool = ...,
+        with_unit: bool = ...,
+        with_city_or_equivalent: bool = ...,
+        with_small_containing_boundaries: bool = ...,
+        with_postal_code: bool = ...,
+        with_latlon: bool = ...,
+        geohash_precision: int = ...,
+        name_and_address_keys: bool = ...,
+        name_only_keys: bool = ...,
+        address_only_keys: bool = ...,
+    ) -> None: ...
+
+class DuplicateOptions:
+    languages: list[str]
+    def __init__(self, *, languages: list[str] = ...) -> None: ...
+
+class FuzzyDuplicateOptions:
+    languages: list[str]
+    needs_review_threshold: float
+    likely_dupe_threshold: float
+    def __init__(
+        self,
+        *,
+        languages: list[str] = ...,
+        needs_review_threshold: float = ...,
+        likely_dupe_threshold: float = ...,
+    ) -> None: ...
+
+def setup(data_dir: Optional[str] = None) -> None: ...
+def parse_address(
+    address: str, language: Optional[str] = None, country: Optional[str] = None
+) -> list[tuple[str, str]]: ...
+def expand_address(address: str, options: Optional[NormalizeOptions] = None) -> list[str]: ...
+def expand_address_root(
+    address: str, options: Optional[NormalizeOptions] = None
+) -> list[str]: ...
+def near_dupe_hashes(
+    components: list[tuple[str, str]],
+    options: Optional[NearDupeHashOptions] = None,
+    languages: Optional[list[str]] = None,
+    latlon: Optional[tuple[float, float]] = None,
+) -> list[str]: ...
+def is_name_duplicate(
+    value1: str, value2: str, options: Optional[DuplicateOptions] = None
+) -> str: ...
+def is_street_duplicate(
+    value1: str, value2: str, options: Optional[DuplicateOptions] = None
+) -> str: ...
+def is_house_number_duplicate(
+    value1: str, value2: str, options: Optional[DuplicateOptions] = None
+) -> str: ...
+def is_po_box_duplicate(
+    value1: str, value2: str, options: Optional[DuplicateOptions] = None
+) -> str: ...
+def is_unit_duplicate(
+    value1: str, value2: str, options: Optional[DuplicateOptions] = None
+) -> str: ...
+def is_floor_duplicate(
+    value1: str, value2: str, options: Optional[DuplicateOptions] = None
+) -> str: ...
+def is_postal_code_duplicate(
+    value1: str, value2: str, options: Optional[DuplicateOptions] = None
+) -> str: ...
+def is_toponym_duplicate(
+    components1: list[tuple[str, str]],
+    components2: list[tuple[str, str]],
+    options: Optional[DuplicateOptions] = None,
+) -> str: ...
+def is_name_duplicate_fuzzy(
+    tokens1: list[tuple[str, float]],
+    tokens2: list[tuple[str, float]],
+    options: Optional[FuzzyDuplicateOptions] = None,
+) -> tuple[str, float]: ...
+def is_street_duplicate_fuzzy(
+    tokens1: list[tuple[str, float]],
+    tokens2: list[tuple[str, float]],
+    options: Optional[FuzzyDuplicateOptions] = None,
+) -> tuple[str, float]: ...
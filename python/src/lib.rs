@@ -0,0 +1,445 @@
+//! Python bindings for the libpostal parser, expander and deduplication functions.
+//!
+//! The libpostal models are loaded by `setup` or on first use, from the directory given in the
+//! `LIBPOSTAL_DATA_DIR` environment variable if set. The GIL is released during libpostal calls.
+
+use std::env;
+use std::ffi::{CString, OsString};
+
+use once_cell::sync::OnceCell;
+use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rpostal::{
+    Address, AddressComponents, AddressParser, AddressParserOptions, Core, DuplicateOptions,
+    FuzzyDuplicateOptions, Language, LanguageClassifier, NearDupeHashOptions, NearDupeRecord,
+    NormalizeOptions,
+};
+
+static CORE: OnceCell<Core> = OnceCell::new();
+static PARSER: OnceCell<AddressParser<'static>> = OnceCell::new();
+static CLASSIFIER: OnceCell<LanguageClassifier<'static>> = OnceCell::new();
+
+fn setup_error(what: &str) -> PyErr {
+    PyRuntimeError::new_err(format!("failed to setup the libpostal {}", what))
+}
+
+fn core(data_dir: Option<&OsString>) -> PyResult<&'static Core> {
+    CORE.get_or_try_init(|| {
+        match data_dir {
+            Some(dir) => Core::setup_datadir(dir),
+            None => Core::setup(),
+        }
+        .ok_or_else(|| setup_error("core"))
+    })
+}
+
+fn parser(data_dir: Option<&OsString>) -> PyResult<&'static AddressParser<'static>> {
+    let core = core(data_dir)?;
+    PARSER.get_or_try_init(|| {
+        match data_dir {
+            Some(dir) => core.setup_parser_datadir(dir),
+            None => core.setup_parser(),
+        }
+        .ok_or_else(|| setup_error("address parser"))
+    })
+}
+
+fn classifier(data_dir: Option<&OsString>) -> PyResult<&'static LanguageClassifier<'static>> {
+    let core = core(data_dir)?;
+    CLASSIFIER.get_or_try_init(|| {
+        match data_dir {
+            Some(dir) => core.setup_language_classifier_datadir(dir),
+            None => core.setup_language_classifier(),
+        }
+        .ok_or_else(|| setup_error("language classifier"))
+    })
+}
+
+fn default_data_dir() -> Option<OsString> {
+    env::var_os("LIBPOSTAL_DATA_DIR")
+}
+
+/// Converts the fields of the options classes between their Python and Rust types.
+trait Bridge<R>: Sized {
+    fn from_rust(value: R) -> Self;
+    fn to_rust(&self) -> PyResult<R>;
+}
+
+impl<T: Copy> Bridge<T> for T {
+    fn from_rust(value: T) -> T {
+        value
+    }
+
+    fn to_rust(&self) -> PyResult<T> {
+        Ok(*self)
+    }
+}
+
+impl Bridge<Vec<Language>> for Vec<String> {
+    fn from_rust(value: Vec<Language>) -> Vec<String> {
+        value.iter().map(|l| l.code().to_owned()).collect()
+    }
+
+    fn to_rust(&self) -> PyResult<Vec<Language>> {
+        self.iter()
+            .map(|l| {
+                l.parse()
+                    .map_err(|e: rpostal::UnknownLanguage| PyValueError::new_err(e.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// Address components are written like `"street|toponym"`.
+impl Bridge<AddressComponents> for String {
+    fn from_rust(value: AddressComponents) -> String {
+        value.to_string()
+    }
+
+    fn to_rust(&self) -> PyResult<AddressComponents> {
+        self.parse()
+            .map_err(|e: rpostal::UnknownAddressComponent| PyValueError::new_err(e.to_string()))
+    }
+}
+
+/// Generates a Python class mirroring a Rust options type. Its constructor takes the fields as
+/// keyword arguments, the missing ones keeping the libpostal defaults.
+macro_rules! options_class {
+    ($(#[$meta:meta])* $py:ident($name:literal) => $rust:ident { $($field:ident: $ty:ty,)* }) => {
+        $(#[$meta])*
+        #[pyclass(module = "rpostal", name = $name, get_all, set_all)]
+        #[derive(Clone)]
+        struct $py {
+            $($field: $ty,)*
+        }
+
+        #[pymethods]
+        impl $py {
+            #[new]
+            #[pyo3(signature = (**kwargs))]
+            fn new(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<$py> {
+                let mut ret = $py::from_rust(&$rust::default());
+                for (key, value) in kwargs.into_iter().flatten() {
+                    match key.extract::<String>()?.as_str() {
+                        $(stringify!($field) => ret.$field = value.extract()?,)*
+                        other => {
+                            return Err(PyTypeError::new_err(format!(
+                                "{}() got an unexpected keyword argument '{}'",
+                                stringify!($rust),
+                                other,
+                            )))
+                        }
+                    }
+                }
+                Ok(ret)
+            }
+
+            fn __repr__(slf: &Bound<'_, $py>) -> PyResult<String> {
+                let fields: Vec<String> = vec![
+                    $(format!(
+                        "{}={}",
+                        stringify!($field),
+                        slf.getattr(stringify!($field))?.repr()?,
+                    ),)*
+                ];
+                Ok(format!("{}({})", stringify!($rust), fields.join(", ")))
+            }
+        }
+
+        impl $py {
+            fn from_rust(options: &$rust) -> $py {
+                $py {
+                    $($field: Bridge::from_rust(options.$field.clone()),)*
+                }
+            }
+
+            fn to_rust(&self) -> PyResult<$rust> {
+                Ok($rust {
+                    $($field: self.$field.to_rust()?,)*
+                })
+            }
+        }
+    };
+}
+
+options_class! {
+    /// Options of `expand_address` and `expand_address_root`.
+    PyNormalizeOptions("NormalizeOptions") => NormalizeOptions {
+        languages: Vec<String>,
+        address_components: String,
+        latin_ascii: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        decompose: bool,
+        lowercase: bool,
+        trim_string: bool,
+        drop_parentheticals: bool,
+        replace_numeric_hyphens: bool,
+        delete_numeric_hyphens: bool,
+        split_alpha_from_numeric: bool,
+        replace_word_hyphens: bool,
+        delete_word_hyphens: bool,
+        delete_final_periods: bool,
+        delete_acronym_periods: bool,
+        drop_english_possessives: bool,
+        delete_apostrophes: bool,
+        expand_numex: bool,
+        roman_numerals: bool,
+    }
+}
+
+options_class! {
+    /// Options of `near_dupe_hashes`. The coordinates are given to `near_dupe_hashes` as `latlon`.
+    PyNearDupeHashOptions("NearDupeHashOptions") => NearDupeHashOptions {
+        with_name: bool,
+        with_address: bool,
+        with_unit: bool,
+        with_city_or_equivalent: bool,
+        with_small_containing_boundaries: bool,
+        with_postal_code: bool,
+        with_latlon: bool,
+        geohash_precision: u32,
+        name_and_address_keys: bool,
+        name_only_keys: bool,
+        address_only_keys: bool,
+    }
+}
+
+options_class! {
+    /// Options of the `is_*_duplicate` functions.
+    PyDuplicateOptions("DuplicateOptions") => DuplicateOptions {
+        languages: Vec<String>,
+    }
+}
+
+options_class! {
+    /// Options of the `is_*_duplicate_fuzzy` functions.
+    PyFuzzyDuplicateOptions("FuzzyDuplicateOptions") => FuzzyDuplicateOptions {
+        languages: Vec<String>,
+        needs_review_threshold: f64,
+        likely_dupe_threshold: f64,
+    }
+}
+
+fn to_addresses(components: Vec<(String, String)>) -> PyResult<Vec<Address>> {
+    components
+        .into_iter()
+        .map(|(label, value)| {
+            Ok(Address {
+                label: CString::new(label)
+                    .map_err(|_| PyValueError::new_err("unexpected NUL in label"))?,
+                value: CString::new(value)
+                    .map_err(|_| PyValueError::new_err("unexpected NUL in value"))?,
+            })
+        })
+        .collect()
+}
+
+fn from_addresses(addresses: Vec<Address>) -> Vec<(String, String)> {
+    addresses
+        .into_iter()
+        .map(|a| {
+            (
+                a.label.to_string_lossy().into_owned(),
+                a.value.to_string_lossy().into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// Loads the libpostal models. Calling it is optional: they're loaded on first use otherwise.
+#[pyfunction]
+#[pyo3(signature = (data_dir=None))]
+fn setup(py: Python<'_>, data_dir: Option<String>) -> PyResult<()> {
+    let data_dir = data_dir.map(OsString::from).or_else(default_data_dir);
+    py.allow_threads(|| {
+        parser(data_dir.as_ref())?;
+        classifier(data_dir.as_ref())?;
+        Ok(())
+    })
+}
+
+/// Returns the `(label, value)` components of `address`.
+#[pyfunction]
+#[pyo3(signature = (address, language=None, country=None))]
+fn parse_address(
+    py: Python<'_>,
+    address: &str,
+    language: Option<String>,
+    country: Option<String>,
+) -> PyResult<Vec<(String, String)>> {
+    let parser = parser(default_data_dir().as_ref())?;
+    let mut options = AddressParserOptions::new();
+    options.set_language(language).set_country(country);
+    let parsed = py.allow_threads(|| parser.parse_address(address, &options));
+    Ok(parsed.map(from_addresses).unwrap_or_default())
+}
+
+fn normalize_options(options: Option<PyRef<'_, PyNormalizeOptions>>) -> PyResult<NormalizeOptions> {
+    match options {
+        Some(o) => o.to_rust(),
+        None => Ok(NormalizeOptions::default()),
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (address, options=None))]
+fn expand_address(
+    py: Python<'_>,
+    address: &str,
+    options: Option<PyRef<'_, PyNormalizeOptions>>,
+) -> PyResult<Vec<String>> {
+    let core = core(default_data_dir().as_ref())?;
+    let options = normalize_options(options)?;
+    Ok(py.allow_threads(|| core.expand_address(address, options)))
+}
+
+#[pyfunction]
+#[pyo3(signature = (address, options=None))]
+fn expand_address_root(
+    py: Python<'_>,
+    address: &str,
+    options: Option<PyRef<'_, PyNormalizeOptions>>,
+) -> PyResult<Vec<String>> {
+    let core = core(default_data_dir().as_ref())?;
+    let options = normalize_options(options)?;
+    Ok(py.allow_threads(|| core.expand_address_root(address, options)))
+}
+
+/// Returns the near-dupe hashes of the `(label, value)` components. If `latlon` is given, the
+/// geohashes of these coordinates are added.
+#[pyfunction]
+#[pyo3(signature = (components, options=None, languages=None, latlon=None))]
+fn near_dupe_hashes(
+    py: Python<'_>,
+    components: Vec<(String, String)>,
+    options: Option<PyRef<'_, PyNearDupeHashOptions>>,
+    languages: Option<Vec<String>>,
+    latlon: Option<(f64, f64)>,
+) -> PyResult<Vec<String>> {
+    let classifier = classifier(default_data_dir().as_ref())?;
+    let addresses = to_addresses(components)?;
+    let languages = languages.unwrap_or_default().to_rust()?;
+    let mut options = match options {
+        Some(o) => o.to_rust()?,
+        None => NearDupeHashOptions::default(),
+    };
+    let mut record = NearDupeRecord::new(&addresses).languages(&languages);
+    if let Some((latitude, longitude)) = latlon {
+        options.with_latlon = true;
+        record = record.latlon(latitude, longitude);
+    }
+    py.allow_threads(|| classifier.near_dupe_hashes_for(&record, &options))
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+fn duplicate_options(options: Option<PyRef<'_, PyDuplicateOptions>>) -> PyResult<DuplicateOptions> {
+    match options {
+        Some(o) => o.to_rust(),
+        None => Ok(DuplicateOptions::default()),
+    }
+}
+
+/// Generates the `is_*_duplicate` functions comparing two values. They return the name of the
+/// `DuplicateStatus`, like `"likely_duplicate"`.
+macro_rules! duplicate_functions {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[pyfunction]
+            #[pyo3(signature = (value1, value2, options=None))]
+            fn $name(
+                py: Python<'_>,
+                value1: &str,
+                value2: &str,
+                options: Option<PyRef<'_, PyDuplicateOptions>>,
+            ) -> PyResult<&'static str> {
+                let classifier = classifier(default_data_dir().as_ref())?;
+                let options = duplicate_options(options)?;
+                Ok(py
+                    .allow_threads(|| classifier.$name(value1, value2, &options))
+                    .name())
+            }
+        )*
+    };
+}
+
+duplicate_functions!(
+    is_name_duplicate,
+    is_street_duplicate,
+    is_house_number_duplicate,
+    is_po_box_duplicate,
+    is_unit_duplicate,
+    is_floor_duplicate,
+    is_postal_code_duplicate,
+);
+
+/// Compares the toponyms (city, state...) of two lists of `(label, value)` components.
+#[pyfunction]
+#[pyo3(signature = (components1, components2, options=None))]
+fn is_toponym_duplicate(
+    py: Python<'_>,
+    components1: Vec<(String, String)>,
+    components2: Vec<(String, String)>,
+    options: Option<PyRef<'_, PyDuplicateOptions>>,
+) -> PyResult<&'static str> {
+    let classifier = classifier(default_data_dir().as_ref())?;
+    let addresses1 = to_addresses(components1)?;
+    let addresses2 = to_addresses(components2)?;
+    let options = duplicate_options(options)?;
+    Ok(py
+        .allow_threads(|| classifier.is_toponym_duplicate(&addresses1, &addresses2, &options))
+        .name())
+}
+
+/// Generates the `is_*_duplicate_fuzzy` functions comparing two lists of `(token, score)`. They
+/// return the name of the `DuplicateStatus` and the similarity.
+macro_rules! fuzzy_duplicate_functions {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[pyfunction]
+            #[pyo3(signature = (tokens1, tokens2, options=None))]
+            fn $name(
+                py: Python<'_>,
+                tokens1: Vec<(String, f64)>,
+                tokens2: Vec<(String, f64)>,
+                options: Option<PyRef<'_, PyFuzzyDuplicateOptions>>,
+            ) -> PyResult<(&'static str, f64)> {
+                let classifier = classifier(default_data_dir().as_ref())?;
+                let options = match options {
+                    Some(o) => o.to_rust()?,
+                    None => FuzzyDuplicateOptions::default(),
+                };
+                let status = py.allow_threads(|| classifier.$name(&tokens1, &tokens2, &options));
+                Ok((status.status().name(), status.similarity()))
+            }
+        )*
+    };
+}
+
+fuzzy_duplicate_functions!(is_name_duplicate_fuzzy, is_street_duplicate_fuzzy);
+
+#[pymodule]
+#[pyo3(name = "rpostal")]
+fn rpostal_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyNormalizeOptions>()?;
+    m.add_class::<PyNearDupeHashOptions>()?;
+    m.add_class::<PyDuplicateOptions>()?;
+    m.add_class::<PyFuzzyDuplicateOptions>()?;
+    m.add_function(wrap_pyfunction!(setup, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_address, m)?)?;
+    m.add_function(wrap_pyfunction!(expand_address, m)?)?;
+    m.add_function(wrap_pyfunction!(expand_address_root, m)?)?;
+    m.add_function(wrap_pyfunction!(near_dupe_hashes, m)?)?;
+    m.add_function(wrap_pyfunction!(is_name_duplicate, m)?)?;
+    m.add_function(wrap_pyfunction!(is_street_duplicate, m)?)?;
+    m.add_function(wrap_pyfunction!(is_house_number_duplicate, m)?)?;
+    m.add_function(wrap_pyfunction!(is_po_box_duplicate, m)?)?;
+    m.add_function(wrap_pyfunction!(is_unit_duplicate, m)?)?;
+    m.add_function(wrap_pyfunction!(is_floor_duplicate, m)?)?;
+    m.add_function(wrap_pyfunction!(is_postal_code_duplicate, m)?)?;
+    m.add_function(wrap_pyfunction!(is_toponym_duplicate, m)?)?;
+    m.add_function(wrap_pyfunction!(is_name_duplicate_fuzzy, m)?)?;
+    m.add_function(wrap_pyfunction!(is_street_duplicate_fuzzy, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,307 @@
+//! SQLite extension exposing the libpostal parser, expander and deduplication functions.
+//!
+//! ```sql
+//! .load ./librpostal_sqlite
+//! SELECT postal_parse(addr, 'road') FROM customers;
+//! SELECT postal_parse_json('30 W 26th St, New York');
+//! SELECT expansion FROM postal_expand('30 W 26th St');
+//! SELECT value FROM json_each(postal_near_dupe_hashes('30 W 26th St, New York'));
+//! SELECT postal_is_duplicate('W 26th St', 'West 26th Street', 'street');
+//! ```
+//!
+//! The libpostal data is loaded once per process, from the directory given in the
+//! `LIBPOSTAL_DATA_DIR` environment variable if set.
+
+use std::borrow::Cow;
+use std::env;
+use std::ffi::{c_int, CStr};
+use std::marker::PhantomData;
+
+use once_cell::sync::OnceCell;
+use rpostal::{
+    Address, AddressParser, AddressParserOptions, Core, DuplicateStatus, LanguageClassifier,
+    ParsedAddress,
+};
+use rusqlite::functions::{Context, FunctionFlags};
+use rusqlite::vtab::{
+    self, Filters, IndexConstraintOp, IndexInfo, Module, VTab, VTabConfig, VTabConnection,
+    VTabCursor,
+};
+use rusqlite::{ffi, Connection, Error, Result};
+
+static CORE: OnceCell<Core> = OnceCell::new();
+static PARSER: OnceCell<AddressParser<'static>> = OnceCell::new();
+static CLASSIFIER: OnceCell<LanguageClassifier<'static>> = OnceCell::new();
+
+fn user_error(message: String) -> Error {
+    Error::UserFunctionError(message.into())
+}
+
+fn core() -> Result<&'static Core> {
+    CORE.get_or_try_init(|| {
+        match env::var_os("LIBPOSTAL_DATA_DIR") {
+            Some(dir) => Core::setup_datadir(dir),
+            None => Core::setup(),
+        }
+        .ok_or_else(|| user_error("failed to setup libpostal".to_owned()))
+    })
+}
+
+fn parser() -> Result<&'static AddressParser<'static>> {
+    let core = core()?;
+    PARSER.get_or_try_init(|| {
+        match env::var_os("LIBPOSTAL_DATA_DIR") {
+            Some(dir) => core.setup_parser_datadir(dir),
+            None => core.setup_parser(),
+        }
+        .ok_or_else(|| user_error("failed to setup the libpostal address parser".to_owned()))
+    })
+}
+
+fn classifier() -> Result<&'static LanguageClassifier<'static>> {
+    let core = core()?;
+    CLASSIFIER.get_or_try_init(|| {
+        match env::var_os("LIBPOSTAL_DATA_DIR") {
+            Some(dir) => core.setup_language_classifier_datadir(dir),
+            None => core.setup_language_classifier(),
+        }
+        .ok_or_else(|| user_error("failed to setup the libpostal language classifier".to_owned()))
+    })
+}
+
+/// Registers the `postal_*` functions on `conn`. This is what the extension entry point does,
+/// and it can be used directly on a `Connection` (an in-memory one in tests for example).
+pub fn register(conn: &Connection) -> Result<()> {
+    // Loads the models now rather than on first call, to report errors when loading.
+    parser()?;
+    classifier()?;
+
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+    conn.create_scalar_function("postal_parse_json", 1, flags, parse_json)?;
+    conn.create_scalar_function("postal_parse_json", 3, flags, parse_json)?;
+    conn.create_scalar_function("postal_parse", 2, flags, parse_label)?;
+    conn.create_scalar_function("postal_near_dupe_hashes", 1, flags, near_dupe_hashes)?;
+    conn.create_scalar_function("postal_is_duplicate", 3, flags, is_duplicate)?;
+
+    const EXPAND: Module<ExpandTab> = Module::eponymous_only_module();
+    conn.create_module(c"postal_expand", &EXPAND, None)
+}
+
+fn parse(address: &str, options: &AddressParserOptions) -> Result<Option<Vec<Address>>> {
+    Ok(parser()?.parse_address(address, options))
+}
+
+/// `postal_parse_json(address [, language, country])`: returns a JSON object mapping each
+/// label to its value.
+fn parse_json(ctx: &Context<'_>) -> Result<Option<String>> {
+    let address = match ctx.get::<Option<String>>(0)? {
+        Some(a) => a,
+        None => return Ok(None),
+    };
+    let mut options = AddressParserOptions::new();
+    if ctx.len() == 3 {
+        options
+            .set_language(ctx.get::<Option<String>>(1)?)
+            .set_country(ctx.get::<Option<String>>(2)?);
+    }
+    let parsed = match parse(&address, &options)? {
+        Some(p) => ParsedAddress::from_addresses(&p),
+        None => return Ok(None),
+    };
+    let object = parsed
+        .iter()
+        .map(|(label, value)| (label.to_owned(), value.into()))
+        .collect::<serde_json::Map<_, _>>();
+    Ok(Some(serde_json::Value::Object(object).to_string()))
+}
+
+/// `postal_parse(address, label)`: returns the value of `label`, or `NULL`.
+fn parse_label(ctx: &Context<'_>) -> Result<Option<String>> {
+    let (address, label) = match (ctx.get::<Option<String>>(0)?, ctx.get::<String>(1)?) {
+        (Some(a), l) => (a, l),
+        (None, _) => return Ok(None),
+    };
+    Ok(
+        parse(&address, &AddressParserOptions::new())?.and_then(|p| {
+            ParsedAddress::from_addresses(&p)
+                .get(&label)
+                .map(|v| v.to_owned())
+        }),
+    )
+}
+
+/// `postal_near_dupe_hashes(address)`: parses `address` and returns its near-dupe hashes as a
+/// JSON array, to be used with `json_each`.
+fn near_dupe_hashes(ctx: &Context<'_>) -> Result<Option<String>> {
+    let address = match ctx.get::<Option<String>>(0)? {
+        Some(a) => a,
+        None => return Ok(None),
+    };
+    let parsed = match parse(&address, &AddressParserOptions::new())? {
+        Some(p) => p,
+        None => return Ok(None),
+    };
+    let classifier = classifier()?;
+    let hashes =
+        classifier.near_dupe_hashes(&parsed, &classifier.get_near_dupe_hash_default_options());
+    Ok(Some(serde_json::Value::from(hashes).to_string()))
+}
+
+/// `postal_is_duplicate(a, b, component)`: returns the `DuplicateStatus` name of the two values
+/// (`non_duplicate`, `likely_duplicate`...). For the `toponym` component, `a` and `b` are whole
+/// addresses which are parsed first.
+fn is_duplicate(ctx: &Context<'_>) -> Result<Option<&'static str>> {
+    let (a, b) = match (ctx.get::<Option<String>>(0)?, ctx.get::<Option<String>>(1)?) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Ok(None),
+    };
+    let component = ctx.get::<String>(2)?;
+    let classifier = classifier()?;
+    let options = classifier.get_default_duplicate_options();
+
+    let status = match component.as_str() {
+        "name" => classifier.is_name_duplicate(&a, &b, &options),
+        "street" | "road" => classifier.is_street_duplicate(&a, &b, &options),
+        "house_number" => classifier.is_house_number_duplicate(&a, &b, &options),
+        "po_box" => classifier.is_po_box_duplicate(&a, &b, &options),
+        "unit" => classifier.is_unit_duplicate(&a, &b, &options),
+        "floor" | "level" => classifier.is_floor_duplicate(&a, &b, &options),
+        "postal_code" | "postcode" => classifier.is_postal_code_duplicate(&a, &b, &options),
+        "toponym" => {
+            let parser = parser()?;
+            let options_parser = AddressParserOptions::new();
+            match (
+                parser.parse_address(&a, &options_parser),
+                parser.parse_address(&b, &options_parser),
+            ) {
+                (Some(a), Some(b)) => classifier.is_toponym_duplicate(&a, &b, &options),
+                _ => DuplicateStatus::Null,
+            }
+        }
+        other => return Err(user_error(format!("unknown component `{}`", other))),
+    };
+    Ok(Some(status.name()))
+}
+
+/// `postal_expand(address)`: table-valued function returning one `expansion` row per variant.
+#[repr(C)]
+struct ExpandTab {
+    /// Base class. Must be first.
+    base: ffi::sqlite3_vtab,
+}
+
+const EXPAND_COLUMN_ADDRESS: c_int = 1;
+
+unsafe impl<'vtab> VTab<'vtab> for ExpandTab {
+    type Aux = ();
+    type Cursor = ExpandTabCursor<'vtab>;
+
+    fn connect(
+        db: &mut VTabConnection,
+        _aux: Option<&()>,
+        _module_name: &[u8],
+        _database_name: &[u8],
+        _table_name: &[u8],
+        _args: &[&[u8]],
+    ) -> Result<(Cow<'static, CStr>, ExpandTab)> {
+        db.config(VTabConfig::Innocuous)?;
+        Ok((
+            Cow::Borrowed(c"CREATE TABLE x(expansion, address hidden)"),
+            ExpandTab {
+                base: ffi::sqlite3_vtab::default(),
+            },
+        ))
+    }
+
+    fn best_index(&self, info: &mut IndexInfo) -> Result<bool> {
+        let address = info.constraints().position(|c| {
+            c.column() == EXPAND_COLUMN_ADDRESS
+                && c.is_usable()
+                && c.operator() == IndexConstraintOp::SQLITE_INDEX_CONSTRAINT_EQ
+        });
+        // Without an address, there is nothing to expand.
+        let address = match address {
+            Some(a) => a,
+            None => return Ok(false),
+        };
+        let mut usage = info.constraint_usage(address);
+        usage.set_argv_index(1);
+        usage.set_omit(true);
+        info.set_estimated_cost(1.);
+        info.set_estimated_rows(10);
+        Ok(true)
+    }
+
+    fn open(&mut self) -> Result<ExpandTabCursor<'_>> {
+        Ok(ExpandTabCursor::default())
+    }
+}
+
+#[derive(Default)]
+#[repr(C)]
+struct ExpandTabCursor<'vtab> {
+    /// Base class. Must be first.
+    base: ffi::sqlite3_vtab_cursor,
+    address: Option<String>,
+    expansions: Vec<String>,
+    pos: usize,
+    phantom: PhantomData<&'vtab ExpandTab>,
+}
+
+unsafe impl VTabCursor for ExpandTabCursor<'_> {
+    fn filter(
+        &mut self,
+        _idx_num: c_int,
+        _idx_str: Option<&str>,
+        args: &Filters<'_>,
+    ) -> Result<()> {
+        self.address = args.get::<Option<String>>(0)?;
+        self.expansions = match self.address {
+            Some(ref address) => {
+                let core = core()?;
+                core.expand_address(address, core.get_default_options())
+            }
+            None => Vec::new(),
+        };
+        self.pos = 0;
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<()> {
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn eof(&self) -> bool {
+        self.pos >= self.expansions.len()
+    }
+
+    fn column(&self, ctx: &mut vtab::Context, i: c_int) -> Result<()> {
+        match i {
+            EXPAND_COLUMN_ADDRESS => ctx.set_result(&self.address),
+            _ => ctx.set_result(&self.expansions[self.pos]),
+        }
+    }
+
+    fn rowid(&self) -> Result<i64> {
+        Ok(self.pos as i64 + 1)
+    }
+}
+
+/// Entry point called by SQLite when loading the extension.
+///
+/// # Safety
+///
+/// Must only be called by SQLite.
+#[cfg(feature = "loadable")]
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_rpostal_init(
+    db: *mut ffi::sqlite3,
+    pz_err_msg: *mut *mut std::ffi::c_char,
+    p_api: *mut ffi::sqlite3_api_routines,
+) -> c_int {
+    Connection::extension_init2(db, pz_err_msg, p_api, |conn| {
+        register(&conn)?;
+        Ok(false)
+    })
+}
@@ -0,0 +1,120 @@
+//! Runs the functions on an in-memory database. SQLite must be linked statically and the
+//! libpostal models installed: `cargo test --no-default-features --features bundled`.
+
+#![cfg(feature = "bundled")]
+
+use rusqlite::types::Value;
+use rusqlite::{Connection, Result};
+
+fn connection() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    rpostal_sqlite::register(&conn).unwrap();
+    conn
+}
+
+fn query<T: rusqlite::types::FromSql>(conn: &Connection, sql: &str) -> Result<T> {
+    conn.query_row(sql, [], |row| row.get(0))
+}
+
+#[test]
+fn parse_label() {
+    let conn = connection();
+    let road: Option<String> = query(
+        &conn,
+        "SELECT postal_parse('781 Franklin Ave Crown Heights Brooklyn NY 11216', 'road')",
+    )
+    .unwrap();
+    assert_eq!(road.as_deref(), Some("franklin ave"));
+    let unit: Option<String> = query(
+        &conn,
+        "SELECT postal_parse('781 Franklin Ave Crown Heights Brooklyn NY 11216', 'unit')",
+    )
+    .unwrap();
+    assert_eq!(unit, None);
+}
+
+#[test]
+fn parse_label_doesnt_read_hints() {
+    // `postal_parse` used to read its label as the language hint and a missing third argument
+    // as the country.
+    let conn = connection();
+    let road: Option<String> =
+        query(&conn, "SELECT postal_parse('10 main street', 'road')").unwrap();
+    assert_eq!(road.as_deref(), Some("main street"));
+    let null: Option<String> = query(&conn, "SELECT postal_parse(NULL, 'road')").unwrap();
+    assert_eq!(null, None);
+}
+
+#[test]
+fn parse_json() {
+    let conn = connection();
+    let json: String = query(
+        &conn,
+        "SELECT postal_parse_json('10 main street springfield')",
+    )
+    .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["house_number"], "10");
+    assert_eq!(json["road"], "main street");
+
+    let json: String = query(
+        &conn,
+        "SELECT postal_parse_json('100 main street ontario', 'en', 'ca')",
+    )
+    .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["state"], "ontario");
+
+    let json: String = query(
+        &conn,
+        "SELECT postal_parse_json('10 main street springfield', NULL, NULL)",
+    )
+    .unwrap();
+    assert!(json.starts_with('{'));
+    let null: Option<String> = query(&conn, "SELECT postal_parse_json(NULL)").unwrap();
+    assert_eq!(null, None);
+}
+
+#[test]
+fn wrong_argument_count() {
+    let conn = connection();
+    assert!(query::<Value>(&conn, "SELECT postal_parse('10 main street')").is_err());
+    assert!(query::<Value>(&conn, "SELECT postal_parse_json('10 main street', 'en')").is_err());
+}
+
+#[test]
+fn expand() {
+    let conn = connection();
+    let mut stmt = conn
+        .prepare("SELECT expansion FROM postal_expand('30 W 26th St')")
+        .unwrap();
+    let expansions = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert!(expansions.iter().any(|e| e == "30 west 26th street"));
+}
+
+#[test]
+fn near_dupe_hashes() {
+    let conn = connection();
+    let count: i64 = query(
+        &conn,
+        "SELECT count(*) FROM json_each(postal_near_dupe_hashes('30 W 26th St, New York'))",
+    )
+    .unwrap();
+    assert!(count > 0);
+}
+
+#[test]
+fn is_duplicate() {
+    let conn = connection();
+    let status: String = query(
+        &conn,
+        "SELECT postal_is_duplicate('W 26th St', 'West 26th Street', 'street')",
+    )
+    .unwrap();
+    assert_eq!(status, "exact_duplicate");
+    assert!(query::<Value>(&conn, "SELECT postal_is_duplicate('a', 'b', 'nope')").is_err());
+}
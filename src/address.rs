@@ -2,6 +2,8 @@ use traits::ToC;
 
 use std::ffi::CString;
 
+use Language;
+
 use libc::c_char;
 
 #[derive(Debug, Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -32,3 +34,32 @@ impl<'a> ToC for &'a [Address] {
         (labels, values)
     }
 }
+
+/// A record for which near-dupe hashes are computed, carrying its own coordinates.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NearDupeRecord<'a> {
+    pub addresses: &'a [Address],
+    pub languages: &'a [Language],
+    /// `(latitude, longitude)`.
+    pub latlon: Option<(f64, f64)>,
+}
+
+impl<'a> NearDupeRecord<'a> {
+    pub fn new(addresses: &'a [Address]) -> NearDupeRecord<'a> {
+        NearDupeRecord {
+            addresses,
+            languages: &[],
+            latlon: None,
+        }
+    }
+
+    pub fn languages(mut self, languages: &'a [Language]) -> NearDupeRecord<'a> {
+        self.languages = languages;
+        self
+    }
+
+    pub fn latlon(mut self, latitude: f64, longitude: f64) -> NearDupeRecord<'a> {
+        self.latlon = Some((latitude, longitude));
+        self
+    }
+}
@@ -1,11 +1,20 @@
+use spans::align;
 use sys;
 use traits::{ToC, ToRust};
+use utf8::{check_strict, cstring_lossy};
+use utils::ptr_to_cstring;
 
 use Address;
 use AddressParserOptions;
 use Core;
+use LossyConversion;
+use ParseHints;
+use ParseResponse;
+use SpanOptions;
+use SpannedComponent;
 
-use std::ffi::CString;
+use std::ffi::{CStr, CString, NulError};
+use std::mem;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
@@ -13,7 +22,6 @@ static INIT_ADDRESS_PARSER: once_cell::sync::Lazy<Arc<Mutex<(usize, Option<CStri
     once_cell::sync::Lazy::new(|| Arc::new(Mutex::new((0, None))));
 
 pub struct AddressParser<'a> {
-    #[allow(dead_code)]
     inner: &'a Core,
 }
 
@@ -69,13 +77,43 @@ impl<'a> AddressParser<'a> {
         unsafe { sys::libpostal_get_address_parser_default_options() }.to_rust()
     }
 
+    /// Parses `address` using the given language and country hints, which can help a lot on
+    /// ambiguous inputs.
+    pub fn parse_address_with(&self, address: &str, hints: ParseHints) -> Option<Vec<Address>> {
+        self.parse_address(address, &AddressParserOptions::from(hints))
+    }
+
     pub fn parse_address(
         &self,
         address: &str,
         options: &AddressParserOptions,
     ) -> Option<Vec<Address>> {
-        let address = address.to_c();
-        let (_, options) = options.to_c();
+        let mut ret = self.parse_address_cstr(&address.to_c(), options)?;
+        for address in ret.iter_mut() {
+            address.label = cstring_lossy(mem::take(&mut address.label));
+            address.value = cstring_lossy(mem::take(&mut address.value));
+        }
+        Some(ret)
+    }
+
+    /// Same as `parse_address` but takes the address as bytes, which don't have to be valid
+    /// UTF-8, and returns the components as is. Fails if `address` contains a NUL byte.
+    pub fn parse_address_bytes(
+        &self,
+        address: &[u8],
+        options: &AddressParserOptions,
+    ) -> Result<Option<Vec<Address>>, NulError> {
+        Ok(self.parse_address_cstr(&CString::new(address)?, options))
+    }
+
+    /// Same as `parse_address_bytes` but takes a C string.
+    pub fn parse_address_cstr(
+        &self,
+        address: &CStr,
+        options: &AddressParserOptions,
+    ) -> Option<Vec<Address>> {
+        // `_strings` owns the strings `options` points to.
+        let (_strings, options) = options.to_c();
 
         let data = unsafe { sys::libpostal_parse_address(address.as_ptr(), options) };
         if data.is_null() {
@@ -88,10 +126,8 @@ impl<'a> AddressParser<'a> {
 
                 for i in 0..data.num_components {
                     ret.push(Address {
-                        label: CString::new((*data.labels.offset(i as _)).to_rust())
-                            .expect("unexpected '\0' in label"),
-                        value: CString::new((*data.components.offset(i as _)).to_rust())
-                            .expect("unexpected '\0' in value"),
+                        label: ptr_to_cstring(*data.labels.add(i)),
+                        value: ptr_to_cstring(*data.components.add(i)),
                     });
                 }
             }
@@ -99,4 +135,52 @@ impl<'a> AddressParser<'a> {
             Some(ret)
         }
     }
+
+    /// Same as `parse_address` but fails if libpostal returns invalid UTF-8 instead of
+    /// replacing it.
+    pub fn parse_address_strict(
+        &self,
+        address: &str,
+        options: &AddressParserOptions,
+    ) -> Result<Option<Vec<Address>>, LossyConversion> {
+        let ret = self.parse_address_cstr(&address.to_c(), options);
+        for address in ret.iter().flatten() {
+            check_strict(&address.label)?;
+            check_strict(&address.value)?;
+        }
+        Ok(ret)
+    }
+
+    /// Same as `parse_address` but doesn't copy the components: they're borrowed from the
+    /// returned `ParseResponse` which frees them when dropped.
+    pub fn parse_address_response(
+        &self,
+        address: &str,
+        options: &AddressParserOptions,
+    ) -> Option<ParseResponse> {
+        let address = address.to_c();
+        // `_strings` owns the strings `options` points to.
+        let (_strings, options) = options.to_c();
+
+        let data = unsafe { sys::libpostal_parse_address(address.as_ptr(), options) };
+        if data.is_null() {
+            return None;
+        }
+        Some(unsafe { ParseResponse::new(data) })
+    }
+
+    /// Same as `parse_address` but also returns where each component is located in `address`.
+    ///
+    /// Since libpostal normalizes the values, this is best-effort: components which couldn't
+    /// be located have no span.
+    pub fn parse_address_spanned(
+        &self,
+        address: &str,
+        options: &AddressParserOptions,
+        span_options: SpanOptions,
+    ) -> Option<Vec<SpannedComponent>> {
+        let response = self.parse_address_response(address, options)?;
+        let tokens = self.inner.tokenize(address, false);
+        Some(align(address, &tokens, response.iter(), span_options))
+    }
 }
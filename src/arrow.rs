@@ -0,0 +1,122 @@
+//! Columnar parsing and expansion of [Apache Arrow](https://arrow.apache.org) string arrays.
+//!
+//! Null addresses (and addresses libpostal failed to parse) give null rows.
+
+use std::sync::Arc;
+
+use arrow_array::builder::{ListBuilder, StringBuilder};
+use arrow_array::{
+    Array, ArrayRef, GenericStringArray, ListArray, OffsetSizeTrait, StringArray, StructArray,
+};
+use arrow_buffer::NullBuffer;
+use arrow_schema::{DataType, Field, Fields};
+
+use AddressParser;
+use AddressParserOptions;
+use Core;
+use NormalizeOptions;
+use ParsedAddress;
+use LABELS;
+
+/// Returns the fields of the `StructArray` built by `parse_addresses`: one nullable `Utf8`
+/// field per label of `LABELS`, in the same order.
+pub fn parsed_address_fields() -> Fields {
+    LABELS
+        .iter()
+        .map(|label| Field::new(*label, DataType::Utf8, true))
+        .collect()
+}
+
+/// Returns the type of the `ListArray` built by `expand_addresses`.
+pub fn expansions_data_type() -> DataType {
+    DataType::List(Arc::new(Field::new_list_field(DataType::Utf8, true)))
+}
+
+/// Parses each address of `addresses`. The returned array has one child column per label (see
+/// `parsed_address_fields`). If a label appears more than once in an address, the values are
+/// joined with a space like in `ParsedAddress::from_addresses`.
+pub fn parse_addresses<O: OffsetSizeTrait>(
+    parser: &AddressParser,
+    addresses: &GenericStringArray<O>,
+    options: &AddressParserOptions,
+) -> StructArray {
+    let mut columns = LABELS
+        .iter()
+        .map(|_| StringBuilder::with_capacity(addresses.len(), 0))
+        .collect::<Vec<_>>();
+    let mut validity = Vec::with_capacity(addresses.len());
+
+    for address in addresses.iter() {
+        let parsed = address
+            .and_then(|a| parser.parse_address(a, options))
+            .map(|a| ParsedAddress::from_addresses(&a));
+        validity.push(parsed.is_some());
+        for (label, column) in LABELS.iter().zip(columns.iter_mut()) {
+            column.append_option(parsed.as_ref().and_then(|p| p.get(label)));
+        }
+    }
+
+    let columns = columns
+        .into_iter()
+        .map(|mut c| Arc::new(c.finish()) as ArrayRef)
+        .collect();
+    StructArray::new(
+        parsed_address_fields(),
+        columns,
+        Some(NullBuffer::from(validity)),
+    )
+}
+
+/// Expands each address of `addresses` with `Core::expand_address`.
+pub fn expand_addresses<O: OffsetSizeTrait>(
+    core: &Core,
+    addresses: &GenericStringArray<O>,
+    options: NormalizeOptions,
+) -> ListArray {
+    let mut builder = ListBuilder::with_capacity(StringBuilder::new(), addresses.len());
+
+    for address in addresses.iter() {
+        match address {
+            Some(address) => {
+                for expansion in core.expand_address(address, options.clone()) {
+                    builder.values().append_value(expansion);
+                }
+                builder.append(true);
+            }
+            None => builder.append(false),
+        }
+    }
+    builder.finish()
+}
+
+/// Same as `parse_addresses` but takes a dynamically typed array, which must be a `Utf8` or
+/// `LargeUtf8` one. Returns `None` otherwise.
+pub fn parse_addresses_dyn(
+    parser: &AddressParser,
+    addresses: &dyn Array,
+    options: &AddressParserOptions,
+) -> Option<StructArray> {
+    let any = addresses.as_any();
+    if let Some(a) = any.downcast_ref::<StringArray>() {
+        Some(parse_addresses(parser, a, options))
+    } else {
+        any.downcast_ref::<GenericStringArray<i64>>()
+            .map(|a| parse_addresses(parser, a, options))
+    }
+}
+
+/// Same as `expand_addresses` but takes a dynamically typed array, which must be a `Utf8` or
+/// `LargeUtf8` one. Returns `None` otherwise.
+pub fn expand_addresses_dyn(
+    core: &Core,
+    addresses: &dyn Array,
+    options: NormalizeOptions,
+) -> Option<ListArray> {
+    let any = addresses.as_any();
+    if let Some(a) = any.downcast_ref::<StringArray>() {
+        Some(expand_addresses(core, a, options))
+    } else {
+        any.downcast_ref::<GenericStringArray<i64>>()
+            .map(|a| expand_addresses(core, a, options))
+    }
+}
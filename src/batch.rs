@@ -0,0 +1,331 @@
+//! Batch processing of Parquet files: reads an address column and writes a file with the parsed
+//! components, the expansions and the near-dupe hashes of each address.
+//!
+//! Files are streamed record batch by record batch, so only `batch_size` input rows and one
+//! output row group are kept in memory at a time.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::builder::{ListBuilder, StringBuilder};
+use arrow_array::{
+    Array, ArrayRef, GenericStringArray, OffsetSizeTrait, RecordBatch, RecordBatchReader,
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::ChunkReader;
+
+use arrow::{expand_addresses, expansions_data_type};
+use AddressParser;
+use AddressParserOptions;
+use Core;
+use LanguageClassifier;
+use NearDupeHashOptions;
+use NormalizeOptions;
+use ParsedAddress;
+use LABELS;
+
+#[derive(Debug)]
+pub enum BatchError {
+    Io(io::Error),
+    Parquet(ParquetError),
+    Arrow(ArrowError),
+    /// The address column isn't in the input file.
+    MissingColumn(String),
+    /// The address column isn't a `Utf8` or `LargeUtf8` one.
+    InvalidColumnType(String, DataType),
+    /// An output column has the same name as an input column.
+    DuplicateColumn(String),
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BatchError::Io(ref e) => write!(f, "I/O error: {}", e),
+            BatchError::Parquet(ref e) => write!(f, "parquet error: {}", e),
+            BatchError::Arrow(ref e) => write!(f, "arrow error: {}", e),
+            BatchError::MissingColumn(ref c) => write!(f, "missing column `{}`", c),
+            BatchError::InvalidColumnType(ref c, ref t) => {
+                write!(f, "column `{}` has type {} instead of a string type", c, t)
+            }
+            BatchError::DuplicateColumn(ref c) => {
+                write!(f, "output column `{}` already exists in the input", c)
+            }
+        }
+    }
+}
+
+impl Error for BatchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            BatchError::Io(ref e) => Some(e),
+            BatchError::Parquet(ref e) => Some(e),
+            BatchError::Arrow(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BatchError {
+    fn from(e: io::Error) -> BatchError {
+        BatchError::Io(e)
+    }
+}
+
+impl From<ParquetError> for BatchError {
+    fn from(e: ParquetError) -> BatchError {
+        BatchError::Parquet(e)
+    }
+}
+
+impl From<ArrowError> for BatchError {
+    fn from(e: ArrowError) -> BatchError {
+        BatchError::Arrow(e)
+    }
+}
+
+/// Counters returned by `ParquetProcessor::process`.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct BatchStats {
+    pub rows: usize,
+    /// Rows whose address was null or couldn't be parsed.
+    pub failed: usize,
+}
+
+/// Parses, expands and hashes the addresses of a Parquet file.
+///
+/// The output file contains the input columns (unless `with_input_columns(false)` is used),
+/// then one `Utf8` column per label of `LABELS` (prefixed with `with_column_prefix`), an
+/// `expansions` list column and a `near_dupe_hashes` list column.
+pub struct ParquetProcessor<'a> {
+    core: &'a Core,
+    parser: &'a AddressParser<'a>,
+    classifier: &'a LanguageClassifier<'a>,
+    address_column: String,
+    column_prefix: String,
+    input_columns: bool,
+    batch_size: usize,
+    row_group_size: usize,
+    parser_options: AddressParserOptions,
+    normalize_options: Option<NormalizeOptions>,
+    near_dupe_options: Option<NearDupeHashOptions>,
+}
+
+impl<'a> ParquetProcessor<'a> {
+    pub fn new(
+        core: &'a Core,
+        parser: &'a AddressParser<'a>,
+        classifier: &'a LanguageClassifier<'a>,
+        address_column: &str,
+    ) -> ParquetProcessor<'a> {
+        ParquetProcessor {
+            core,
+            parser,
+            classifier,
+            address_column: address_column.to_owned(),
+            column_prefix: String::new(),
+            input_columns: true,
+            batch_size: 8192,
+            row_group_size: 1024 * 1024,
+            parser_options: AddressParserOptions::new(),
+            normalize_options: Some(core.get_default_options()),
+            near_dupe_options: Some(classifier.get_near_dupe_hash_default_options()),
+        }
+    }
+
+    /// Prefix of the component columns, for example `"parsed_"` to get `parsed_road`.
+    pub fn with_column_prefix(mut self, prefix: &str) -> ParquetProcessor<'a> {
+        self.column_prefix = prefix.to_owned();
+        self
+    }
+
+    /// Copies the input columns in the output file. Enabled by default.
+    pub fn with_input_columns(mut self, input_columns: bool) -> ParquetProcessor<'a> {
+        self.input_columns = input_columns;
+        self
+    }
+
+    /// Number of rows read and processed at once.
+    pub fn with_batch_size(mut self, batch_size: usize) -> ParquetProcessor<'a> {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Maximum number of rows of the output row groups.
+    pub fn with_row_group_size(mut self, row_group_size: usize) -> ParquetProcessor<'a> {
+        self.row_group_size = row_group_size.max(1);
+        self
+    }
+
+    pub fn with_parser_options(mut self, options: AddressParserOptions) -> ParquetProcessor<'a> {
+        self.parser_options = options;
+        self
+    }
+
+    /// Options given to `Core::expand_address`. If `None`, there is no `expansions` column.
+    pub fn with_expansions(mut self, options: Option<NormalizeOptions>) -> ParquetProcessor<'a> {
+        self.normalize_options = options;
+        self
+    }
+
+    /// Options given to `LanguageClassifier::near_dupe_hashes`. If `None`, there is no
+    /// `near_dupe_hashes` column.
+    pub fn with_near_dupe_hashes(
+        mut self,
+        options: Option<NearDupeHashOptions>,
+    ) -> ParquetProcessor<'a> {
+        self.near_dupe_options = options;
+        self
+    }
+
+    pub fn process_files<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        input: P,
+        output: Q,
+    ) -> Result<BatchStats, BatchError> {
+        self.process(File::open(input)?, File::create(output)?)
+    }
+
+    /// Reads the Parquet data of `input` and writes the result into `output`.
+    pub fn process<R, W>(&self, input: R, output: W) -> Result<BatchStats, BatchError>
+    where
+        R: ChunkReader + 'static,
+        W: Write + Send,
+    {
+        let reader = ParquetRecordBatchReaderBuilder::try_new(input)?
+            .with_batch_size(self.batch_size)
+            .build()?;
+        let input_schema = reader.schema();
+        let address_type = match input_schema.field_with_name(&self.address_column) {
+            Ok(field) => field.data_type().clone(),
+            Err(_) => return Err(BatchError::MissingColumn(self.address_column.clone())),
+        };
+        if address_type != DataType::Utf8 && address_type != DataType::LargeUtf8 {
+            return Err(BatchError::InvalidColumnType(
+                self.address_column.clone(),
+                address_type,
+            ));
+        }
+
+        let schema = self.output_schema(&input_schema)?;
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(self.row_group_size)
+            .build();
+        let mut writer = ArrowWriter::try_new(output, schema.clone(), Some(properties))?;
+        let mut stats = BatchStats::default();
+
+        for batch in reader {
+            let batch = batch?;
+            let addresses = batch
+                .column_by_name(&self.address_column)
+                .expect("address column checked above");
+            let mut columns = if self.input_columns {
+                batch.columns().to_vec()
+            } else {
+                Vec::new()
+            };
+            if address_type == DataType::Utf8 {
+                self.process_column::<i32>(addresses, &mut columns, &mut stats);
+            } else {
+                self.process_column::<i64>(addresses, &mut columns, &mut stats);
+            }
+            writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+        }
+        writer.close()?;
+        Ok(stats)
+    }
+
+    fn output_schema(&self, input: &Schema) -> Result<SchemaRef, BatchError> {
+        let mut fields = if self.input_columns {
+            input.fields().iter().cloned().collect()
+        } else {
+            Vec::new()
+        };
+        let mut added = LABELS
+            .iter()
+            .map(|label| {
+                Field::new(
+                    format!("{}{}", self.column_prefix, label),
+                    DataType::Utf8,
+                    true,
+                )
+            })
+            .collect::<Vec<_>>();
+        if self.normalize_options.is_some() {
+            added.push(Field::new("expansions", expansions_data_type(), true));
+        }
+        if self.near_dupe_options.is_some() {
+            added.push(Field::new("near_dupe_hashes", expansions_data_type(), true));
+        }
+        for field in added {
+            if fields.iter().any(|f| f.name() == field.name()) {
+                return Err(BatchError::DuplicateColumn(field.name().clone()));
+            }
+            fields.push(Arc::new(field));
+        }
+        Ok(Arc::new(Schema::new(fields)))
+    }
+
+    fn process_column<O: OffsetSizeTrait>(
+        &self,
+        addresses: &ArrayRef,
+        columns: &mut Vec<ArrayRef>,
+        stats: &mut BatchStats,
+    ) {
+        let addresses = addresses
+            .as_any()
+            .downcast_ref::<GenericStringArray<O>>()
+            .expect("address column type checked above");
+        let mut components = LABELS
+            .iter()
+            .map(|_| StringBuilder::with_capacity(addresses.len(), 0))
+            .collect::<Vec<_>>();
+        let mut hashes = ListBuilder::new(StringBuilder::new());
+
+        for address in addresses.iter() {
+            let parsed = address.and_then(|a| self.parser.parse_address(a, &self.parser_options));
+            stats.rows += 1;
+            if parsed.is_none() {
+                stats.failed += 1;
+            }
+            let record = parsed.as_ref().map(|p| ParsedAddress::from_addresses(p));
+            for (label, builder) in LABELS.iter().zip(components.iter_mut()) {
+                builder.append_option(record.as_ref().and_then(|r| r.get(label)));
+            }
+            if let Some(ref options) = self.near_dupe_options {
+                match parsed {
+                    Some(ref parsed) => {
+                        for hash in self.classifier.near_dupe_hashes(parsed, options) {
+                            hashes.values().append_value(hash);
+                        }
+                        hashes.append(true);
+                    }
+                    None => hashes.append(false),
+                }
+            }
+        }
+
+        columns.extend(
+            components
+                .into_iter()
+                .map(|mut builder| Arc::new(builder.finish()) as ArrayRef),
+        );
+        if let Some(ref options) = self.normalize_options {
+            columns.push(Arc::new(expand_addresses(
+                self.core,
+                addresses,
+                options.clone(),
+            )));
+        }
+        if self.near_dupe_options.is_some() {
+            columns.push(Arc::new(hashes.finish()));
+        }
+    }
+}
@@ -0,0 +1,597 @@
+//! C API of the libpostal bindings, for embedding them in C or C++ programs. Enabled with the
+//! `capi` feature. The shared library and its header are built with
+//! [cargo-c](https://github.com/lu-zero/cargo-c): `cargo cbuild --features capi`. A copy of the
+//! header is kept in `include/rpostal.h`, checked against the generated one by the tests.
+//!
+//! All the functions take a handle created with `rpostal_new`. Results are returned as JSON
+//! strings which must be freed with `rpostal_string_free`. On error, `NULL` is returned and
+//! `rpostal_last_error` gives the reason.
+//!
+//! Options are given as JSON objects (or `NULL` for the defaults) with the fields of the
+//! corresponding Rust options types, missing fields keeping their default value.
+//!
+//! Parsed addresses are cached by the handle (see `rpostal_set_cache_capacity`), so parsing the
+//! same address again, directly or through `rpostal_compare`, doesn't call libpostal.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::mem::ManuallyDrop;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::{Mutex, MutexGuard};
+
+use libc::c_char;
+use serde::Deserialize;
+use serde_json::{self, json, Value};
+
+#[cfg(feature = "format")]
+use format::Formatter;
+use Address;
+use AddressParser;
+use AddressParserOptions;
+use Core;
+use Country;
+use DuplicateOptions;
+use DuplicateStatus;
+use LanguageClassifier;
+use NearDupeHashOptions;
+use NearDupeRecord;
+use NormalizeOptions;
+use ParseQuality;
+use ParsedAddress;
+
+/// Major version of the C API. Incremented on incompatible changes.
+pub const RPOSTAL_CAPI_VERSION_MAJOR: u32 = 1;
+/// Minor version of the C API. Incremented when functions are added.
+pub const RPOSTAL_CAPI_VERSION_MINOR: u32 = 0;
+
+/// Number of parsed addresses cached by a new handle.
+pub const RPOSTAL_DEFAULT_CACHE_CAPACITY: usize = 4096;
+
+/// Loaded libpostal models. Created with `rpostal_new` and freed with `rpostal_free`.
+///
+/// A handle can be used from several threads at once.
+pub struct RpostalHandle {
+    parser: ManuallyDrop<AddressParser<'static>>,
+    classifier: ManuallyDrop<LanguageClassifier<'static>>,
+    #[cfg(feature = "format")]
+    formatter: Formatter,
+    cache: Mutex<ParseCache>,
+    // Leaked in `rpostal_new` for `parser` and `classifier` to borrow it.
+    core: &'static Core,
+}
+
+impl Drop for RpostalHandle {
+    fn drop(&mut self) {
+        unsafe {
+            ManuallyDrop::drop(&mut self.parser);
+            ManuallyDrop::drop(&mut self.classifier);
+            drop(Box::from_raw(self.core as *const Core as *mut Core));
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', "\\0")).expect("NUL replaced above");
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, converting its result into a C string and its error (or panic) into the last
+/// error.
+fn ffi_call<F: FnOnce() -> Result<Value, String>>(f: F) -> *mut c_char {
+    let result = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(_) => Err("panic in rpostal".to_owned()),
+    };
+    match result {
+        Ok(value) => {
+            LAST_ERROR.with(|e| *e.borrow_mut() = None);
+            CString::new(value.to_string())
+                .expect("JSON strings escape NUL")
+                .into_raw()
+        }
+        Err(message) => {
+            set_last_error(message);
+            ptr::null_mut()
+        }
+    }
+}
+
+unsafe fn handle<'a>(handle: *const RpostalHandle) -> Result<&'a RpostalHandle, String> {
+    handle.as_ref().ok_or_else(|| "null handle".to_owned())
+}
+
+unsafe fn string<'a>(s: *const c_char, name: &str) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err(format!("null `{}`", name));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| format!("`{}` isn't valid UTF-8", name))
+}
+
+/// Deserializes the options `json`, or returns `default` if it's `NULL`.
+unsafe fn options<T>(json: *const c_char, default: T) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    if json.is_null() {
+        return Ok(default);
+    }
+    serde_json::from_str(string(json, "options")?).map_err(|e| format!("invalid options: {}", e))
+}
+
+/// Deserializes a `[{"label": ..., "value": ...}]` JSON array.
+unsafe fn components(json: *const c_char) -> Result<Vec<Address>, String> {
+    #[derive(Deserialize)]
+    struct Component {
+        label: String,
+        value: String,
+    }
+
+    let components: Vec<Component> = serde_json::from_str(string(json, "components")?)
+        .map_err(|e| format!("invalid components: {}", e))?;
+    components
+        .into_iter()
+        .map(|c| {
+            Ok(Address {
+                label: CString::new(c.label).map_err(|_| "unexpected NUL in label".to_owned())?,
+                value: CString::new(c.value).map_err(|_| "unexpected NUL in value".to_owned())?,
+            })
+        })
+        .collect()
+}
+
+fn components_to_json(addresses: &[Address]) -> Value {
+    addresses
+        .iter()
+        .map(|a| {
+            json!({
+                "label": a.label.to_string_lossy(),
+                "value": a.value.to_string_lossy(),
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Default, Deserialize, Hash, PartialEq, Eq)]
+#[serde(default)]
+struct ParseOptions {
+    language: Option<String>,
+    country: Option<Country>,
+}
+
+// Parse results by address and options. Rather than tracking the least recently used entries,
+// it's simply emptied once full.
+struct ParseCache {
+    capacity: usize,
+    entries: HashMap<(String, ParseOptions), Vec<Address>>,
+}
+
+impl ParseCache {
+    fn new(capacity: usize) -> ParseCache {
+        ParseCache {
+            capacity,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn get(&self, address: &str, options: &ParseOptions) -> Option<Vec<Address>> {
+        self.entries
+            .get(&(address.to_owned(), options.clone()))
+            .cloned()
+    }
+
+    fn insert(&mut self, address: &str, options: &ParseOptions, parsed: &[Address]) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            self.entries.clear();
+        }
+        self.entries
+            .insert((address.to_owned(), options.clone()), parsed.to_vec());
+    }
+}
+
+// `NearDupeHashOptions` along with the coordinates of the record.
+#[derive(Deserialize)]
+struct NearDupeOptions {
+    #[serde(flatten)]
+    options: NearDupeHashOptions,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+impl RpostalHandle {
+    fn parse(&self, address: &str, options: &ParseOptions) -> Result<Vec<Address>, String> {
+        if let Some(parsed) = self.cache().get(address, options) {
+            return Ok(parsed);
+        }
+        let mut parser_options = AddressParserOptions::new();
+        parser_options
+            .set_language(options.language.clone())
+            .set_country(options.country.map(|c| c.code().to_lowercase()));
+        let parsed = self
+            .parser
+            .parse_address(address, &parser_options)
+            .ok_or_else(|| "failed to parse the address".to_owned())?;
+        self.cache().insert(address, options, &parsed);
+        Ok(parsed)
+    }
+
+    fn cache(&self) -> MutexGuard<'_, ParseCache> {
+        // The cache is never left half-updated, so it's fine to use it after a panic.
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_duplicate(
+        &self,
+        component: &str,
+        value1: &str,
+        value2: &str,
+        options: &DuplicateOptions,
+    ) -> Result<DuplicateStatus, String> {
+        let classifier = &self.classifier;
+        Ok(match component {
+            "name" => classifier.is_name_duplicate(value1, value2, options),
+            "street" | "road" => classifier.is_street_duplicate(value1, value2, options),
+            "house_number" => classifier.is_house_number_duplicate(value1, value2, options),
+            "po_box" => classifier.is_po_box_duplicate(value1, value2, options),
+            "unit" => classifier.is_unit_duplicate(value1, value2, options),
+            "floor" | "level" => classifier.is_floor_duplicate(value1, value2, options),
+            "postal_code" | "postcode" => {
+                classifier.is_postal_code_duplicate(value1, value2, options)
+            }
+            "toponym" => {
+                let parse_options = ParseOptions::default();
+                let address1 = self.parse(value1, &parse_options)?;
+                let address2 = self.parse(value2, &parse_options)?;
+                classifier.is_toponym_duplicate(&address1, &address2, options)
+            }
+            other => return Err(format!("unknown component `{}`", other)),
+        })
+    }
+}
+
+/// Returns the version of the C API as `major << 16 | minor`, to check it against the
+/// `RPOSTAL_CAPI_VERSION_*` constants of the header the program was compiled with.
+#[no_mangle]
+pub extern "C" fn rpostal_capi_version() -> u32 {
+    (RPOSTAL_CAPI_VERSION_MAJOR << 16) | RPOSTAL_CAPI_VERSION_MINOR
+}
+
+/// Loads the libpostal models from `data_dir`, or from the default directory if it's `NULL`.
+/// Returns `NULL` on failure.
+///
+/// # Safety
+///
+/// `data_dir` must be `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rpostal_new(data_dir: *const c_char) -> *mut RpostalHandle {
+    let result = panic::catch_unwind(|| {
+        let data_dir = match data_dir.is_null() {
+            true => None,
+            false => Some(string(data_dir, "data_dir")?),
+        };
+        let core = match data_dir {
+            Some(dir) => Core::setup_datadir(dir),
+            None => Core::setup(),
+        }
+        .ok_or_else(|| "failed to setup libpostal".to_owned())?;
+        let core: &'static Core = Box::leak(Box::new(core));
+        let models = match data_dir {
+            Some(dir) => core
+                .setup_parser_datadir(dir)
+                .zip(core.setup_language_classifier_datadir(dir)),
+            None => core.setup_parser().zip(core.setup_language_classifier()),
+        };
+        let (parser, classifier) = match models {
+            Some(models) => models,
+            None => {
+                drop(Box::from_raw(core as *const Core as *mut Core));
+                return Err("failed to setup the libpostal models".to_owned());
+            }
+        };
+        Ok(Box::into_raw(Box::new(RpostalHandle {
+            parser: ManuallyDrop::new(parser),
+            classifier: ManuallyDrop::new(classifier),
+            #[cfg(feature = "format")]
+            formatter: Formatter::bundled(),
+            cache: Mutex::new(ParseCache::new(RPOSTAL_DEFAULT_CACHE_CAPACITY)),
+            core,
+        })))
+    });
+    match result {
+        Ok(Ok(handle)) => handle,
+        Ok(Err(message)) => {
+            set_last_error(message);
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error("panic in rpostal".to_owned());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees a handle returned by `rpostal_new`. Does nothing if `handle` is `NULL`.
+///
+/// # Safety
+///
+/// `handle` must be `NULL` or a handle returned by `rpostal_new` which wasn't freed yet and
+/// isn't used by another thread.
+#[no_mangle]
+pub unsafe extern "C" fn rpostal_free(handle: *mut RpostalHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Sets how many parsed addresses `handle` caches, `0` disabling the cache. The cache is
+/// emptied. Returns `false` if `handle` is `NULL`.
+///
+/// # Safety
+///
+/// `handle` must be `NULL` or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn rpostal_set_cache_capacity(
+    handle: *const RpostalHandle,
+    capacity: usize,
+) -> bool {
+    match self::handle(handle) {
+        Ok(handle) => {
+            *handle.cache() = ParseCache::new(capacity);
+            true
+        }
+        Err(message) => {
+            set_last_error(message);
+            false
+        }
+    }
+}
+
+/// Frees a string returned by this library. Does nothing if `s` is `NULL`.
+///
+/// # Safety
+///
+/// `s` must be `NULL` or a string returned by this library which wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rpostal_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Returns the error of the last call of the current thread which returned `NULL`, or `NULL`
+/// if it succeeded. The string is owned by the library and valid until the next call.
+#[no_mangle]
+pub extern "C" fn rpostal_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Parses `address`. `options` is `NULL` or `{"language": "en", "country": "us"}`.
+///
+/// Returns `{"components": [{"label": ..., "value": ...}], "quality": {"score": ...,
+/// "warnings": [{"code": ..., "message": ...}]}}`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, `address` a valid NUL-terminated string and `options`
+/// `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rpostal_parse(
+    handle: *const RpostalHandle,
+    address: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let handle = self::handle(handle)?;
+        let address = string(address, "address")?;
+        let options = self::options(options, ParseOptions::default())?;
+        let parsed = handle.parse(address, &options)?;
+        let quality = ParseQuality::assess(address, &parsed, options.country);
+        let warnings = quality
+            .warnings
+            .iter()
+            .map(|w| json!({"code": w.code(), "message": w.to_string()}))
+            .collect::<Vec<_>>();
+        Ok(json!({
+            "components": components_to_json(&parsed),
+            "quality": {"score": quality.score, "warnings": warnings},
+        }))
+    })
+}
+
+/// Expands `address` into its normalized variants. `options` is `NULL` or a
+/// `NormalizeOptions` object. Returns a JSON array of strings.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, `address` a valid NUL-terminated string and `options`
+/// `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rpostal_expand(
+    handle: *const RpostalHandle,
+    address: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let handle = self::handle(handle)?;
+        let address = string(address, "address")?;
+        let options: NormalizeOptions = self::options(options, handle.core.get_default_options())?;
+        Ok(handle.core.expand_address(address, options).into())
+    })
+}
+
+/// Same as `rpostal_expand` but only returns the root forms of the address.
+///
+/// # Safety
+///
+/// Same as `rpostal_expand`.
+#[no_mangle]
+pub unsafe extern "C" fn rpostal_expand_root(
+    handle: *const RpostalHandle,
+    address: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let handle = self::handle(handle)?;
+        let address = string(address, "address")?;
+        let options: NormalizeOptions = self::options(options, handle.core.get_default_options())?;
+        Ok(handle.core.expand_address_root(address, options).into())
+    })
+}
+
+/// Computes the near-dupe hashes of a parsed address. `components` is a
+/// `[{"label": ..., "value": ...}]` array (like the `components` returned by `rpostal_parse`)
+/// and `options` is `NULL` or a `NearDupeHashOptions` object, which can also have the `latitude`
+/// and `longitude` of the address for `with_latlon`. Returns a JSON array of strings.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, `components` a valid NUL-terminated string and `options`
+/// `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rpostal_near_dupe_hashes(
+    handle: *const RpostalHandle,
+    components: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let handle = self::handle(handle)?;
+        let addresses = self::components(components)?;
+        let default = NearDupeOptions {
+            options: handle.classifier.get_near_dupe_hash_default_options(),
+            latitude: None,
+            longitude: None,
+        };
+        let options: NearDupeOptions = self::options(options, default)?;
+        let mut record = NearDupeRecord::new(&addresses);
+        if let (Some(latitude), Some(longitude)) = (options.latitude, options.longitude) {
+            record = record.latlon(latitude, longitude);
+        }
+        handle
+            .classifier
+            .near_dupe_hashes_for(&record, &options.options)
+            .map(Value::from)
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Compares two values of `component` (`name`, `street`, `house_number`, `po_box`, `unit`,
+/// `floor`, `postal_code` or `toponym`, for which the values are whole addresses parsed
+/// first). `options` is `NULL` or `{"languages": ["en"]}`.
+///
+/// Returns the status name as a JSON string, for example `"likely_duplicate"`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, `component`, `value1` and `value2` valid NUL-terminated
+/// strings and `options` `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rpostal_is_duplicate(
+    handle: *const RpostalHandle,
+    component: *const c_char,
+    value1: *const c_char,
+    value2: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let handle = self::handle(handle)?;
+        let component = string(component, "component")?;
+        let value1 = string(value1, "value1")?;
+        let value2 = string(value2, "value2")?;
+        let options: DuplicateOptions =
+            self::options(options, handle.classifier.get_default_duplicate_options())?;
+        let status = handle.is_duplicate(component, value1, value2, &options)?;
+        Ok(status.name().into())
+    })
+}
+
+/// Parses both addresses and compares each component present in both of them.
+///
+/// Returns an object mapping each compared component to its status name, for example
+/// `{"house_number": "exact_duplicate", "street": "likely_duplicate"}`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, `address1` and `address2` valid NUL-terminated strings and
+/// `options` `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rpostal_compare(
+    handle: *const RpostalHandle,
+    address1: *const c_char,
+    address2: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let handle = self::handle(handle)?;
+        let parse_options = ParseOptions::default();
+        let address1 = handle.parse(string(address1, "address1")?, &parse_options)?;
+        let address2 = handle.parse(string(address2, "address2")?, &parse_options)?;
+        let options: DuplicateOptions =
+            self::options(options, handle.classifier.get_default_duplicate_options())?;
+        let parsed1 = ParsedAddress::from_addresses(&address1);
+        let parsed2 = ParsedAddress::from_addresses(&address2);
+
+        let mut statuses = serde_json::Map::new();
+        for &(component, label) in &[
+            ("name", "house"),
+            ("house_number", "house_number"),
+            ("street", "road"),
+            ("unit", "unit"),
+            ("floor", "level"),
+            ("po_box", "po_box"),
+            ("postal_code", "postcode"),
+        ] {
+            if let (Some(a), Some(b)) = (parsed1.get(label), parsed2.get(label)) {
+                let status = handle.is_duplicate(component, a, b, &options)?;
+                statuses.insert(component.to_owned(), status.name().into());
+            }
+        }
+        let status = handle
+            .classifier
+            .is_toponym_duplicate(&address1, &address2, &options);
+        if status != DuplicateStatus::Null {
+            statuses.insert("toponym".to_owned(), status.name().into());
+        }
+        Ok(Value::Object(statuses))
+    })
+}
+
+/// Formats a parsed address as a postal label using the address conventions of `country` (an
+/// ISO 3166-1 alpha-2 code, or `NULL` for the default template). `components` is a
+/// `[{"label": ..., "value": ...}]` array. Returns the label as a JSON string.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, `components` a valid NUL-terminated string and `country`
+/// `NULL` or a valid NUL-terminated string.
+#[cfg(feature = "format")]
+#[no_mangle]
+pub unsafe extern "C" fn rpostal_format(
+    handle: *const RpostalHandle,
+    components: *const c_char,
+    country: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let handle = self::handle(handle)?;
+        let addresses = self::components(components)?;
+        let country = match country.is_null() {
+            true => None,
+            false => Some(
+                string(country, "country")?
+                    .parse::<Country>()
+                    .map_err(|e| e.to_string())?,
+            ),
+        };
+        let parsed = ParsedAddress::from_addresses(&addresses);
+        Ok(handle.formatter.format(&parsed, country).into())
+    })
+}
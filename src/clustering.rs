@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use DuplicateStatus;
+
+/// An edge which didn't reach the merge threshold but still needs a human to look at it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReviewEdge<Id> {
+    pub id1: Id,
+    pub id2: Id,
+    pub status: DuplicateStatus,
+}
+
+/// Builds duplicate clusters out of pairwise `DuplicateStatus` results.
+///
+/// Records are merged together (using a union-find) when their status is at least
+/// `min_status`. `PossibleDuplicateNeedsReview` edges which don't reach it are kept aside in a
+/// review queue instead of being dropped.
+#[derive(Clone, Debug)]
+pub struct ClusterBuilder<Id> {
+    min_status: DuplicateStatus,
+    ids: Vec<Id>,
+    indexes: HashMap<Id, usize>,
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+    review: Vec<ReviewEdge<Id>>,
+}
+
+impl<Id: Clone + Eq + Hash> ClusterBuilder<Id> {
+    pub fn new(min_status: DuplicateStatus) -> ClusterBuilder<Id> {
+        ClusterBuilder {
+            min_status,
+            ids: Vec::new(),
+            indexes: HashMap::new(),
+            parents: Vec::new(),
+            ranks: Vec::new(),
+            review: Vec::new(),
+        }
+    }
+
+    /// Registers a record without any edge. It'll end up in its own cluster unless an edge
+    /// merges it with another one.
+    pub fn add_record(&mut self, id: Id) -> &mut ClusterBuilder<Id> {
+        self.index_of(id);
+        self
+    }
+
+    /// Registers the result of a pairwise comparison.
+    pub fn add(&mut self, id1: Id, id2: Id, status: DuplicateStatus) -> &mut ClusterBuilder<Id> {
+        let pos1 = self.index_of(id1.clone());
+        let pos2 = self.index_of(id2.clone());
+
+        if status >= self.min_status {
+            self.union(pos1, pos2);
+        } else if status == DuplicateStatus::PossibleDuplicateNeedsReview {
+            self.review.push(ReviewEdge { id1, id2, status });
+        }
+        self
+    }
+
+    pub fn build(mut self) -> Clusters<Id> {
+        let mut roots = HashMap::new();
+        let mut members: Vec<Vec<Id>> = Vec::new();
+        let mut assignments = HashMap::with_capacity(self.ids.len());
+
+        // Cluster ids are given in the order their first record was seen so that the same input
+        // stream always gives the same ids.
+        for pos in 0..self.ids.len() {
+            let root = self.find(pos);
+            let cluster = *roots.entry(root).or_insert_with(|| {
+                members.push(Vec::new());
+                members.len() - 1
+            });
+            members[cluster].push(self.ids[pos].clone());
+            assignments.insert(self.ids[pos].clone(), cluster);
+        }
+
+        let review = self
+            .review
+            .into_iter()
+            .filter(|edge| assignments.get(&edge.id1) != assignments.get(&edge.id2))
+            .collect();
+
+        Clusters {
+            members,
+            assignments,
+            review,
+        }
+    }
+
+    fn index_of(&mut self, id: Id) -> usize {
+        if let Some(pos) = self.indexes.get(&id) {
+            return *pos;
+        }
+        let pos = self.ids.len();
+        self.ids.push(id.clone());
+        self.indexes.insert(id, pos);
+        self.parents.push(pos);
+        self.ranks.push(0);
+        pos
+    }
+
+    fn find(&mut self, mut pos: usize) -> usize {
+        while self.parents[pos] != pos {
+            // Path halving.
+            self.parents[pos] = self.parents[self.parents[pos]];
+            pos = self.parents[pos];
+        }
+        pos
+    }
+
+    fn union(&mut self, pos1: usize, pos2: usize) {
+        let root1 = self.find(pos1);
+        let root2 = self.find(pos2);
+
+        if root1 == root2 {
+            return;
+        }
+        if self.ranks[root1] < self.ranks[root2] {
+            self.parents[root1] = root2;
+        } else if self.ranks[root1] > self.ranks[root2] {
+            self.parents[root2] = root1;
+        } else {
+            self.parents[root2] = root1;
+            self.ranks[root1] += 1;
+        }
+    }
+}
+
+impl<Id: Clone + Eq + Hash> Extend<(Id, Id, DuplicateStatus)> for ClusterBuilder<Id> {
+    fn extend<I: IntoIterator<Item = (Id, Id, DuplicateStatus)>>(&mut self, iter: I) {
+        for (id1, id2, status) in iter {
+            self.add(id1, id2, status);
+        }
+    }
+}
+
+/// The result of `ClusterBuilder::build`.
+#[derive(Clone, Debug)]
+pub struct Clusters<Id> {
+    members: Vec<Vec<Id>>,
+    assignments: HashMap<Id, usize>,
+    review: Vec<ReviewEdge<Id>>,
+}
+
+impl<Id: Clone + Eq + Hash> Clusters<Id> {
+    /// Returns the number of clusters (records without any duplicate have their own cluster).
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Returns the cluster id of the given record.
+    pub fn cluster_of(&self, id: &Id) -> Option<usize> {
+        self.assignments.get(id).cloned()
+    }
+
+    /// Returns the records of the given cluster, in the order they were first seen.
+    pub fn members(&self, cluster: usize) -> Option<&[Id]> {
+        self.members.get(cluster).map(|m| m.as_slice())
+    }
+
+    /// Iterates over all clusters, the index being the cluster id.
+    pub fn iter(&self) -> impl Iterator<Item = &[Id]> {
+        self.members.iter().map(|m| m.as_slice())
+    }
+
+    /// Iterates over `(record, cluster id)` pairs, ordered by cluster id.
+    pub fn assignments(&self) -> impl Iterator<Item = (&Id, usize)> {
+        self.members
+            .iter()
+            .enumerate()
+            .flat_map(|(cluster, m)| m.iter().map(move |id| (id, cluster)))
+    }
+
+    /// Returns the `PossibleDuplicateNeedsReview` edges between records which ended up in
+    /// different clusters.
+    pub fn review_queue(&self) -> &[ReviewEdge<Id>] {
+        &self.review
+    }
+
+    /// Returns the canonical record of each cluster (indexed by cluster id), picked by `choose`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `choose` returns an index out of the cluster it was given.
+    pub fn canonical_records<F>(&self, mut choose: F) -> Vec<&Id>
+    where
+        F: FnMut(&[Id]) -> usize,
+    {
+        self.members
+            .iter()
+            .map(|m| {
+                let pos = choose(m);
+                assert!(pos < m.len(), "canonical record index out of bounds");
+                &m[pos]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use DuplicateStatus::*;
+
+    fn clusters(edges: &[(u32, u32, DuplicateStatus)]) -> Clusters<u32> {
+        let mut builder = ClusterBuilder::new(LikelyDuplicate);
+        for id in 1..=6 {
+            builder.add_record(id);
+        }
+        builder.extend(edges.iter().cloned());
+        builder.build()
+    }
+
+    #[test]
+    fn merges_from_min_status() {
+        let clusters = clusters(&[
+            (1, 2, LikelyDuplicate),
+            (2, 3, ExactDuplicate),
+            (4, 5, NonDuplicate),
+            (5, 6, Null),
+        ]);
+
+        assert_eq!(clusters.len(), 4);
+        assert_eq!(clusters.members(0), Some(&[1, 2, 3][..]));
+        assert_eq!(clusters.members(1), Some(&[4][..]));
+        assert_eq!(clusters.members(2), Some(&[5][..]));
+        assert_eq!(clusters.members(3), Some(&[6][..]));
+        assert_eq!(clusters.members(4), None);
+        assert!(clusters.review_queue().is_empty());
+    }
+
+    #[test]
+    fn review_edges_are_queued_and_not_merged() {
+        let clusters = clusters(&[
+            (1, 2, PossibleDuplicateNeedsReview),
+            (3, 4, PossibleDuplicateNeedsReview),
+            (3, 4, LikelyDuplicate),
+        ]);
+
+        assert_ne!(clusters.cluster_of(&1), clusters.cluster_of(&2));
+        // 3 and 4 got merged by another edge, so there's nothing left to review.
+        assert_eq!(
+            clusters.review_queue(),
+            &[ReviewEdge {
+                id1: 1,
+                id2: 2,
+                status: PossibleDuplicateNeedsReview,
+            }]
+        );
+    }
+
+    #[test]
+    fn cluster_ids_dont_depend_on_the_edge_order() {
+        let mut edges = vec![
+            (1, 4, LikelyDuplicate),
+            (6, 2, ExactDuplicate),
+            (4, 6, LikelyDuplicate),
+            (3, 5, NonDuplicate),
+        ];
+        let expected = clusters(&edges)
+            .assignments()
+            .map(|(id, c)| (*id, c))
+            .collect::<Vec<_>>();
+
+        edges.reverse();
+        let reversed = clusters(&edges);
+        assert_eq!(
+            reversed
+                .assignments()
+                .map(|(id, c)| (*id, c))
+                .collect::<Vec<_>>(),
+            expected
+        );
+        edges.swap(0, 2);
+        let swapped = clusters(&edges);
+        assert_eq!(
+            swapped
+                .assignments()
+                .map(|(id, c)| (*id, c))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn assignments_and_members_agree() {
+        let clusters = clusters(&[(5, 1, LikelyDuplicate), (2, 6, ExactDuplicate)]);
+
+        assert_eq!(
+            clusters
+                .assignments()
+                .map(|(id, c)| (*id, c))
+                .collect::<Vec<_>>(),
+            vec![(1, 0), (5, 0), (2, 1), (6, 1), (3, 2), (4, 3)]
+        );
+        for (id, cluster) in clusters.assignments() {
+            assert_eq!(clusters.cluster_of(id), Some(cluster));
+            assert!(clusters.members(cluster).unwrap().contains(id));
+        }
+        assert_eq!(clusters.iter().count(), clusters.len());
+        assert_eq!(clusters.cluster_of(&7), None);
+    }
+
+    #[test]
+    fn canonical_records_uses_the_chosen_index() {
+        let clusters = clusters(&[(1, 2, LikelyDuplicate), (2, 3, LikelyDuplicate)]);
+
+        assert_eq!(
+            clusters.canonical_records(|m| m.len() - 1),
+            vec![&3, &4, &5, &6]
+        );
+        assert_eq!(clusters.canonical_records(|_| 0), vec![&1, &4, &5, &6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "canonical record index out of bounds")]
+    fn canonical_records_panics_on_a_bad_index() {
+        clusters(&[]).canonical_records(|m| m.len());
+    }
+}
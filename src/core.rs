@@ -1,15 +1,24 @@
-use std::ffi::CString;
+use std::ffi::{CStr, CString, NulError};
 use std::marker::PhantomData;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
+use libc::{c_char, size_t};
 use sys;
 use traits::{ToC, ToRust};
-use utils::ptr_to_rust;
+use utf8::{into_string_lossy, into_string_strict};
+use utils::ptr_to_cstrings;
 
+use expansion::Expansions;
+use language_classifier::classify_language;
+use parsed_address::component_for_label;
+
+use AddressComponents;
 use AddressParser;
 use LanguageClassifier;
+use LossyConversion;
 use NormalizeOptions;
+use ParsedAddress;
 
 static INIT_CORE: once_cell::sync::Lazy<Arc<Mutex<(usize, Option<CString>)>>> =
     once_cell::sync::Lazy::new(|| Arc::new(Mutex::new((0, None))));
@@ -90,30 +99,136 @@ impl Core {
     }
 
     pub fn expand_address(&self, input: &str, options: NormalizeOptions) -> Vec<String> {
-        let input = input.to_c();
-        let (_, options) = options.to_c();
-        let mut size = 0;
-
-        let ptr = unsafe { sys::libpostal_expand_address(input.as_ptr(), options, &mut size) };
-        let ret = ptr_to_rust(ptr, size);
-        // Apparently we have to free memory of a char** using THIS function so let's go...
-        unsafe {
-            sys::libpostal_expansion_array_destroy(ptr, size);
-        }
-        ret
+        self.expand_address_cstr(&input.to_c(), options)
+            .into_iter()
+            .map(into_string_lossy)
+            .collect()
+    }
+
+    /// Same as `expand_address` but takes the input as bytes, which don't have to be valid
+    /// UTF-8, and returns the expansions as is. Fails if `input` contains a NUL byte.
+    pub fn expand_address_bytes(
+        &self,
+        input: &[u8],
+        options: NormalizeOptions,
+    ) -> Result<Vec<CString>, NulError> {
+        Ok(self.expand_address_cstr(&CString::new(input)?, options))
+    }
+
+    /// Same as `expand_address_bytes` but takes a C string.
+    pub fn expand_address_cstr(&self, input: &CStr, options: NormalizeOptions) -> Vec<CString> {
+        expand(sys::libpostal_expand_address, input, options)
+    }
+
+    /// Same as `expand_address` but fails if libpostal returns invalid UTF-8 instead of
+    /// replacing it.
+    pub fn expand_address_strict(
+        &self,
+        input: &str,
+        options: NormalizeOptions,
+    ) -> Result<Vec<String>, LossyConversion> {
+        self.expand_address_cstr(&input.to_c(), options)
+            .into_iter()
+            .map(into_string_strict)
+            .collect()
     }
 
     pub fn expand_address_root(&self, input: &str, options: NormalizeOptions) -> Vec<String> {
-        let input = input.to_c();
-        let (_, options) = options.to_c();
-        let mut size = 0;
-
-        let ptr = unsafe { sys::libpostal_expand_address_root(input.as_ptr(), options, &mut size) };
-        let ret = ptr_to_rust(ptr, size);
-        // Apparently we have to free memory of a char** using THIS function so let's go...
-        unsafe {
-            sys::libpostal_expansion_array_destroy(ptr, size);
-        }
-        ret
+        self.expand_address_root_cstr(&input.to_c(), options)
+            .into_iter()
+            .map(into_string_lossy)
+            .collect()
+    }
+
+    /// Same as `expand_address_bytes` for `expand_address_root`.
+    pub fn expand_address_root_bytes(
+        &self,
+        input: &[u8],
+        options: NormalizeOptions,
+    ) -> Result<Vec<CString>, NulError> {
+        Ok(self.expand_address_root_cstr(&CString::new(input)?, options))
+    }
+
+    /// Same as `expand_address_cstr` for `expand_address_root`.
+    pub fn expand_address_root_cstr(
+        &self,
+        input: &CStr,
+        options: NormalizeOptions,
+    ) -> Vec<CString> {
+        expand(sys::libpostal_expand_address_root, input, options)
+    }
+
+    /// Same as `expand_address_strict` for `expand_address_root`.
+    pub fn expand_address_root_strict(
+        &self,
+        input: &str,
+        options: NormalizeOptions,
+    ) -> Result<Vec<String>, LossyConversion> {
+        self.expand_address_root_cstr(&input.to_c(), options)
+            .into_iter()
+            .map(into_string_strict)
+            .collect()
+    }
+
+    /// Same as `expand_address` but ranks the expansions and gives information about where they
+    /// come from.
+    ///
+    /// Each expansion is matched with the root expansion (see `expand_address_root`) it reduces
+    /// to, the tokens it has on top of it being the dictionary phrases found in `input`.
+    /// Expansions with a root are ranked first, in the order of their root, then the libpostal
+    /// order is kept.
+    pub fn expand_address_detailed(&self, input: &str, options: NormalizeOptions) -> Expansions {
+        let languages = if !options.languages.is_empty() {
+            options.languages.iter().map(|l| (l.clone(), 1.)).collect()
+        } else if LanguageClassifier::is_initialized() {
+            classify_language(input)
+        } else {
+            Vec::new()
+        };
+        let roots = self.expand_address_root(input, options.clone());
+        Expansions::new(self.expand_address(input, options), roots, languages)
+    }
+
+    /// Expands each field of `address` separately, restricting `options.address_components` to
+    /// the component matching the field (`Street` for `road`, `Toponym` for `city`, etc).
+    ///
+    /// Returns the `(label, expansions)` of each set field, in `LABELS` order.
+    pub fn expand_components(
+        &self,
+        address: &ParsedAddress,
+        options: NormalizeOptions,
+    ) -> Vec<(&'static str, Vec<String>)> {
+        address
+            .iter()
+            .map(|(label, value)| {
+                let mut options = options.clone();
+                if let Some(component) = component_for_label(label) {
+                    let mut components = AddressComponents::new();
+                    components.add(component);
+                    options.address_components = components;
+                }
+                (label, self.expand_address(value, options))
+            })
+            .collect()
+    }
+}
+
+type ExpandFn = unsafe extern "C" fn(
+    *const c_char,
+    sys::libpostal_normalize_options_t,
+    *mut size_t,
+) -> *mut *mut c_char;
+
+fn expand(f: ExpandFn, input: &CStr, options: NormalizeOptions) -> Vec<CString> {
+    // `_languages` owns the strings `options` points to.
+    let (_languages, options) = options.to_c();
+    let mut size = 0;
+
+    let ptr = unsafe { f(input.as_ptr(), options, &mut size) };
+    let ret = ptr_to_cstrings(ptr, size);
+    // Apparently we have to free memory of a char** using THIS function so let's go...
+    unsafe {
+        sys::libpostal_expansion_array_destroy(ptr, size);
     }
+    ret
 }
@@ -0,0 +1,411 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use Language;
+
+// ISO 3166-1 alpha-2 codes (sorted) with the languages commonly used in addresses of the country,
+// most used first. Languages unknown to libpostal aren't listed.
+static COUNTRIES: &[(&str, &[Language])] = &[
+    ("AD", &[Language::Catalan]),
+    ("AE", &[Language::Arabic]),
+    ("AF", &[Language::Persian]),
+    ("AG", &[Language::English]),
+    ("AI", &[Language::English]),
+    ("AL", &[Language::Albanian]),
+    ("AM", &[Language::Armenian]),
+    ("AO", &[Language::Portuguese]),
+    ("AQ", &[]),
+    ("AR", &[Language::Spanish]),
+    ("AS", &[Language::English]),
+    ("AT", &[Language::German]),
+    ("AU", &[Language::English]),
+    ("AW", &[Language::Dutch]),
+    ("AX", &[Language::Swedish]),
+    ("AZ", &[Language::Azerbaijani]),
+    (
+        "BA",
+        &[Language::Bosnian, Language::Croatian, Language::Serbian],
+    ),
+    ("BB", &[Language::English]),
+    ("BD", &[Language::Bengali]),
+    ("BE", &[Language::Dutch, Language::French, Language::German]),
+    ("BF", &[Language::French]),
+    ("BG", &[Language::Bulgarian]),
+    ("BH", &[Language::Arabic]),
+    ("BI", &[Language::French]),
+    ("BJ", &[Language::French]),
+    ("BL", &[Language::French]),
+    ("BM", &[Language::English]),
+    ("BN", &[Language::Malay]),
+    ("BO", &[Language::Spanish]),
+    ("BQ", &[Language::Dutch]),
+    ("BR", &[Language::Portuguese]),
+    ("BS", &[Language::English]),
+    ("BT", &[Language::English]),
+    ("BV", &[Language::NorwegianBokmal]),
+    ("BW", &[Language::English]),
+    ("BY", &[Language::Belarusian, Language::Russian]),
+    ("BZ", &[Language::English]),
+    ("CA", &[Language::English, Language::French]),
+    ("CC", &[Language::English]),
+    ("CD", &[Language::French]),
+    ("CF", &[Language::French]),
+    ("CG", &[Language::French]),
+    (
+        "CH",
+        &[Language::German, Language::French, Language::Italian],
+    ),
+    ("CI", &[Language::French]),
+    ("CK", &[Language::English]),
+    ("CL", &[Language::Spanish]),
+    ("CM", &[Language::French, Language::English]),
+    ("CN", &[Language::Chinese]),
+    ("CO", &[Language::Spanish]),
+    ("CR", &[Language::Spanish]),
+    ("CU", &[Language::Spanish]),
+    ("CV", &[Language::Portuguese]),
+    ("CW", &[Language::Dutch]),
+    ("CX", &[Language::English]),
+    ("CY", &[Language::Greek, Language::Turkish]),
+    ("CZ", &[Language::Czech]),
+    ("DE", &[Language::German]),
+    ("DJ", &[Language::French, Language::Arabic]),
+    ("DK", &[Language::Danish]),
+    ("DM", &[Language::English]),
+    ("DO", &[Language::Spanish]),
+    ("DZ", &[Language::Arabic, Language::French]),
+    ("EC", &[Language::Spanish]),
+    ("EE", &[Language::Estonian]),
+    ("EG", &[Language::Arabic]),
+    ("EH", &[Language::Arabic]),
+    ("ER", &[Language::Arabic, Language::English]),
+    (
+        "ES",
+        &[
+            Language::Spanish,
+            Language::Catalan,
+            Language::Galician,
+            Language::Basque,
+        ],
+    ),
+    ("ET", &[Language::English]),
+    ("FI", &[Language::Finnish, Language::Swedish]),
+    ("FJ", &[Language::English]),
+    ("FK", &[Language::English]),
+    ("FM", &[Language::English]),
+    ("FO", &[Language::Danish]),
+    ("FR", &[Language::French]),
+    ("GA", &[Language::French]),
+    (
+        "GB",
+        &[Language::English, Language::Welsh, Language::ScottishGaelic],
+    ),
+    ("GD", &[Language::English]),
+    ("GE", &[Language::Georgian]),
+    ("GF", &[Language::French]),
+    ("GG", &[Language::English]),
+    ("GH", &[Language::English]),
+    ("GI", &[Language::English]),
+    ("GL", &[Language::Danish]),
+    ("GM", &[Language::English]),
+    ("GN", &[Language::French]),
+    ("GP", &[Language::French]),
+    (
+        "GQ",
+        &[Language::Spanish, Language::French, Language::Portuguese],
+    ),
+    ("GR", &[Language::Greek]),
+    ("GS", &[Language::English]),
+    ("GT", &[Language::Spanish]),
+    ("GU", &[Language::English]),
+    ("GW", &[Language::Portuguese]),
+    ("GY", &[Language::English]),
+    ("HK", &[Language::Chinese, Language::English]),
+    ("HM", &[Language::English]),
+    ("HN", &[Language::Spanish]),
+    ("HR", &[Language::Croatian]),
+    ("HT", &[Language::French]),
+    ("HU", &[Language::Hungarian]),
+    ("ID", &[Language::Indonesian]),
+    ("IE", &[Language::English, Language::Irish]),
+    ("IL", &[Language::Hebrew, Language::Arabic]),
+    ("IM", &[Language::English]),
+    ("IN", &[Language::Hindi, Language::English]),
+    ("IO", &[Language::English]),
+    ("IQ", &[Language::Arabic, Language::Kurdish]),
+    ("IR", &[Language::Persian]),
+    ("IS", &[Language::Icelandic]),
+    ("IT", &[Language::Italian]),
+    ("JE", &[Language::English]),
+    ("JM", &[Language::English]),
+    ("JO", &[Language::Arabic]),
+    ("JP", &[Language::Japanese]),
+    ("KE", &[Language::Swahili, Language::English]),
+    ("KG", &[Language::Russian]),
+    ("KH", &[]),
+    ("KI", &[Language::English]),
+    ("KM", &[Language::Arabic, Language::French]),
+    ("KN", &[Language::English]),
+    ("KP", &[Language::Korean]),
+    ("KR", &[Language::Korean]),
+    ("KW", &[Language::Arabic]),
+    ("KY", &[Language::English]),
+    ("KZ", &[Language::Kazakh, Language::Russian]),
+    ("LA", &[]),
+    ("LB", &[Language::Arabic, Language::French]),
+    ("LC", &[Language::English]),
+    ("LI", &[Language::German]),
+    ("LK", &[Language::Tamil]),
+    ("LR", &[Language::English]),
+    ("LS", &[Language::English]),
+    ("LT", &[Language::Lithuanian]),
+    (
+        "LU",
+        &[Language::Luxembourgish, Language::French, Language::German],
+    ),
+    ("LV", &[Language::Latvian]),
+    ("LY", &[Language::Arabic]),
+    ("MA", &[Language::Arabic, Language::French]),
+    ("MC", &[Language::French]),
+    ("MD", &[Language::Romanian]),
+    ("ME", &[Language::Serbian]),
+    ("MF", &[Language::French]),
+    ("MG", &[Language::Malagasy, Language::French]),
+    ("MH", &[Language::English]),
+    ("MK", &[Language::Macedonian, Language::Albanian]),
+    ("ML", &[Language::French]),
+    ("MM", &[Language::Burmese]),
+    ("MN", &[]),
+    ("MO", &[Language::Chinese, Language::Portuguese]),
+    ("MP", &[Language::English]),
+    ("MQ", &[Language::French]),
+    ("MR", &[Language::Arabic]),
+    ("MS", &[Language::English]),
+    ("MT", &[Language::Maltese, Language::English]),
+    ("MU", &[Language::English, Language::French]),
+    ("MV", &[]),
+    ("MW", &[Language::English]),
+    ("MX", &[Language::Spanish]),
+    ("MY", &[Language::Malay]),
+    ("MZ", &[Language::Portuguese]),
+    ("NA", &[Language::English, Language::Afrikaans]),
+    ("NC", &[Language::French]),
+    ("NE", &[Language::French]),
+    ("NF", &[Language::English]),
+    ("NG", &[Language::English]),
+    ("NI", &[Language::Spanish]),
+    ("NL", &[Language::Dutch]),
+    (
+        "NO",
+        &[Language::NorwegianBokmal, Language::NorwegianNynorsk],
+    ),
+    ("NP", &[]),
+    ("NR", &[Language::English]),
+    ("NU", &[Language::English]),
+    ("NZ", &[Language::English]),
+    ("OM", &[Language::Arabic]),
+    ("PA", &[Language::Spanish]),
+    ("PE", &[Language::Spanish]),
+    ("PF", &[Language::French]),
+    ("PG", &[Language::English]),
+    ("PH", &[Language::Tagalog, Language::English]),
+    ("PK", &[Language::Urdu, Language::English]),
+    ("PL", &[Language::Polish]),
+    ("PM", &[Language::French]),
+    ("PN", &[Language::English]),
+    ("PR", &[Language::Spanish, Language::English]),
+    ("PS", &[Language::Arabic]),
+    ("PT", &[Language::Portuguese]),
+    ("PW", &[Language::English]),
+    ("PY", &[Language::Spanish]),
+    ("QA", &[Language::Arabic]),
+    ("RE", &[Language::French]),
+    ("RO", &[Language::Romanian]),
+    ("RS", &[Language::Serbian]),
+    ("RU", &[Language::Russian]),
+    ("RW", &[Language::French, Language::English]),
+    ("SA", &[Language::Arabic]),
+    ("SB", &[Language::English]),
+    ("SC", &[Language::French, Language::English]),
+    ("SD", &[Language::Arabic, Language::English]),
+    ("SE", &[Language::Swedish]),
+    (
+        "SG",
+        &[
+            Language::English,
+            Language::Malay,
+            Language::Chinese,
+            Language::Tamil,
+        ],
+    ),
+    ("SH", &[Language::English]),
+    ("SI", &[Language::Slovenian]),
+    ("SJ", &[Language::NorwegianBokmal]),
+    ("SK", &[Language::Slovak]),
+    ("SL", &[Language::English]),
+    ("SM", &[Language::Italian]),
+    ("SN", &[Language::French]),
+    ("SO", &[Language::Arabic]),
+    ("SR", &[Language::Dutch]),
+    ("SS", &[Language::English]),
+    ("ST", &[Language::Portuguese]),
+    ("SV", &[Language::Spanish]),
+    ("SX", &[Language::Dutch, Language::English]),
+    ("SY", &[Language::Arabic]),
+    ("SZ", &[Language::English]),
+    ("TC", &[Language::English]),
+    ("TD", &[Language::French, Language::Arabic]),
+    ("TF", &[Language::French]),
+    ("TG", &[Language::French]),
+    ("TH", &[Language::Thai]),
+    ("TJ", &[Language::Russian]),
+    ("TK", &[Language::English]),
+    ("TL", &[Language::Portuguese]),
+    ("TM", &[Language::Russian]),
+    ("TN", &[Language::Arabic, Language::French]),
+    ("TO", &[Language::English]),
+    ("TR", &[Language::Turkish]),
+    ("TT", &[Language::English]),
+    ("TV", &[Language::English]),
+    ("TW", &[Language::Chinese]),
+    ("TZ", &[Language::Swahili, Language::English]),
+    ("UA", &[Language::Ukrainian]),
+    ("UG", &[Language::English, Language::Swahili]),
+    ("UM", &[Language::English]),
+    ("US", &[Language::English]),
+    ("UY", &[Language::Spanish]),
+    ("UZ", &[Language::Uzbek]),
+    ("VA", &[Language::Italian, Language::Latin]),
+    ("VC", &[Language::English]),
+    ("VE", &[Language::Spanish]),
+    ("VG", &[Language::English]),
+    ("VI", &[Language::English]),
+    ("VN", &[Language::Vietnamese]),
+    ("VU", &[Language::English, Language::French]),
+    ("WF", &[Language::French]),
+    ("WS", &[Language::English]),
+    ("YE", &[Language::Arabic]),
+    ("YT", &[Language::French]),
+    ("ZA", &[Language::English, Language::Afrikaans]),
+    ("ZM", &[Language::English]),
+    ("ZW", &[Language::English]),
+];
+
+/// An ISO 3166-1 alpha-2 country code.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Country {
+    index: u16,
+}
+
+impl Country {
+    /// Returns all the countries.
+    pub fn all() -> impl Iterator<Item = Country> {
+        (0..COUNTRIES.len()).map(|index| Country {
+            index: index as u16,
+        })
+    }
+
+    /// Returns the uppercase ISO 3166-1 alpha-2 code of the country.
+    pub fn code(&self) -> &'static str {
+        COUNTRIES[self.index as usize].0
+    }
+
+    /// Returns the languages used in addresses of this country, most used first. It can be
+    /// empty if none of them is supported by libpostal.
+    pub fn default_languages(&self) -> &'static [Language] {
+        COUNTRIES[self.index as usize].1
+    }
+
+    /// Returns the lowercase code, which is what libpostal expects.
+    pub(crate) fn libpostal_code(&self) -> String {
+        self.code().to_lowercase()
+    }
+}
+
+impl fmt::Debug for Country {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Country({})", self.code())
+    }
+}
+
+impl fmt::Display for Country {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownCountry(pub String);
+
+impl fmt::Display for UnknownCountry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown country code {:?}", self.0)
+    }
+}
+
+impl Error for UnknownCountry {}
+
+impl FromStr for Country {
+    type Err = UnknownCountry;
+
+    /// Parses an ISO 3166-1 alpha-2 code (case-insensitive).
+    fn from_str(s: &str) -> Result<Country, UnknownCountry> {
+        let code = s.trim().to_uppercase();
+        COUNTRIES
+            .binary_search_by(|&(c, _)| c.cmp(code.as_str()))
+            .map(|index| Country {
+                index: index as u16,
+            })
+            .map_err(|_| UnknownCountry(s.to_owned()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Country {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Country {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Country, D::Error> {
+        use serde::de::Error;
+
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn countries_are_sorted() {
+        // `from_str` relies on it for its binary search.
+        assert_eq!(COUNTRIES.len(), 249);
+        for pair in COUNTRIES.windows(2) {
+            assert!(pair[0].0 < pair[1].0, "{} >= {}", pair[0].0, pair[1].0);
+        }
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for country in Country::all() {
+            let code = country.code();
+            assert!(code.len() == 2 && code.chars().all(|c| c.is_ascii_uppercase()));
+            assert_eq!(code.parse(), Ok(country));
+            assert_eq!(code.to_lowercase().parse(), Ok(country));
+        }
+        assert_eq!(
+            "XX".parse::<Country>(),
+            Err(UnknownCountry("XX".to_owned()))
+        );
+    }
+}
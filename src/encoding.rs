@@ -0,0 +1,93 @@
+//! Transcoding of addresses in legacy encodings (Latin-1, Windows-1252, Shift_JIS...) into the
+//! UTF-8 libpostal expects, with [encoding_rs](https://docs.rs/encoding_rs).
+//!
+//! Encodings follow the [WHATWG Encoding Standard](https://encoding.spec.whatwg.org), so
+//! `Encoding::for_label(b"latin1")` (and `b"iso-8859-1"`) is Windows-1252, in which every byte
+//! sequence is valid.
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+pub use encoding_rs::Encoding;
+
+/// `transcode_strict` input which isn't valid in the given encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MalformedInput {
+    pub encoding: &'static Encoding,
+}
+
+impl fmt::Display for MalformedInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "input isn't valid {}", self.encoding.name())
+    }
+}
+
+impl Error for MalformedInput {}
+
+/// Decodes `bytes` from `encoding`, replacing malformed sequences with `U+FFFD`. A BOM isn't
+/// treated specially. Doesn't copy `bytes` if they're already valid UTF-8 (or ASCII for an
+/// ASCII-compatible encoding).
+pub fn transcode<'a>(bytes: &'a [u8], encoding: &'static Encoding) -> Cow<'a, str> {
+    encoding.decode_without_bom_handling(bytes).0
+}
+
+/// Same as `transcode` but fails on malformed sequences instead of replacing them.
+pub fn transcode_strict<'a>(
+    bytes: &'a [u8],
+    encoding: &'static Encoding,
+) -> Result<Cow<'a, str>, MalformedInput> {
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .ok_or(MalformedInput { encoding })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{SHIFT_JIS, WINDOWS_1252};
+
+    #[test]
+    fn latin1_is_windows_1252() {
+        assert_eq!(Encoding::for_label(b"latin1"), Some(WINDOWS_1252));
+        assert_eq!(Encoding::for_label(b"iso-8859-1"), Some(WINDOWS_1252));
+    }
+
+    #[test]
+    fn windows_1252() {
+        assert_eq!(transcode_strict(b"caf\xe9", WINDOWS_1252).unwrap(), "café");
+        assert_eq!(transcode_strict(b"\x80 5", WINDOWS_1252).unwrap(), "€ 5");
+        // Every byte is valid, even the ones Windows-1252 doesn't define.
+        let all = (0..=255).collect::<Vec<u8>>();
+        assert!(transcode_strict(&all, WINDOWS_1252).is_ok());
+        assert!(matches!(
+            transcode(b"main street", WINDOWS_1252),
+            Cow::Borrowed("main street")
+        ));
+    }
+
+    #[test]
+    fn shift_jis() {
+        assert_eq!(
+            transcode_strict(b"\x93\x8c\x8b\x9e", SHIFT_JIS).unwrap(),
+            "東京"
+        );
+        // A lead byte followed by a byte which can't be a trail byte, and a truncated sequence.
+        for malformed in &[&b"\x93\x20"[..], &b"\x93\x8c\x8b"[..]] {
+            assert_eq!(
+                transcode_strict(malformed, SHIFT_JIS),
+                Err(MalformedInput {
+                    encoding: SHIFT_JIS
+                })
+            );
+            assert!(transcode(malformed, SHIFT_JIS).contains('\u{fffd}'));
+        }
+        assert_eq!(
+            MalformedInput {
+                encoding: SHIFT_JIS
+            }
+            .to_string(),
+            "input isn't valid Shift_JIS"
+        );
+    }
+}
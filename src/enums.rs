@@ -1,3 +1,7 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
 use sys;
 use traits::{ToC, ToRust};
 
@@ -42,6 +46,76 @@ impl ToC for AddressComponent {
     }
 }
 
+impl AddressComponent {
+    /// Components matching a single bit, in bit order.
+    pub(crate) const SINGLE: &'static [AddressComponent] = &[
+        AddressComponent::Any,
+        AddressComponent::Name,
+        AddressComponent::HouseNumber,
+        AddressComponent::Street,
+        AddressComponent::Unit,
+        AddressComponent::Level,
+        AddressComponent::Staircase,
+        AddressComponent::Entrance,
+        AddressComponent::Category,
+        AddressComponent::Near,
+        AddressComponent::Toponym,
+        AddressComponent::PostalCode,
+        AddressComponent::POBox,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            AddressComponent::Any => "any",
+            AddressComponent::Name => "name",
+            AddressComponent::HouseNumber => "house_number",
+            AddressComponent::Street => "street",
+            AddressComponent::Unit => "unit",
+            AddressComponent::Level => "level",
+            AddressComponent::Staircase => "staircase",
+            AddressComponent::Entrance => "entrance",
+            AddressComponent::Category => "category",
+            AddressComponent::Near => "near",
+            AddressComponent::Toponym => "toponym",
+            AddressComponent::PostalCode => "postal_code",
+            AddressComponent::POBox => "po_box",
+            AddressComponent::All => "all",
+        }
+    }
+}
+
+impl fmt::Display for AddressComponent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownAddressComponent(pub String);
+
+impl fmt::Display for UnknownAddressComponent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown address component {:?}", self.0)
+    }
+}
+
+impl Error for UnknownAddressComponent {}
+
+impl FromStr for AddressComponent {
+    type Err = UnknownAddressComponent;
+
+    /// Parses the name returned by `AddressComponent::name` (case-insensitive).
+    fn from_str(s: &str) -> Result<AddressComponent, UnknownAddressComponent> {
+        let name = s.trim().to_lowercase();
+        AddressComponent::SINGLE
+            .iter()
+            .chain(Some(&AddressComponent::All))
+            .find(|c| c.name() == name)
+            .cloned()
+            .ok_or_else(|| UnknownAddressComponent(s.to_owned()))
+    }
+}
+
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum DuplicateStatus {
     Null,
@@ -51,6 +125,24 @@ pub enum DuplicateStatus {
     ExactDuplicate,
 }
 
+impl DuplicateStatus {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            DuplicateStatus::Null => "null",
+            DuplicateStatus::NonDuplicate => "non_duplicate",
+            DuplicateStatus::PossibleDuplicateNeedsReview => "possible_duplicate_needs_review",
+            DuplicateStatus::LikelyDuplicate => "likely_duplicate",
+            DuplicateStatus::ExactDuplicate => "exact_duplicate",
+        }
+    }
+}
+
+impl fmt::Display for DuplicateStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 impl ToC for DuplicateStatus {
     type Out = sys::libpostal_duplicate_status_t;
 
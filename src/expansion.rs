@@ -0,0 +1,202 @@
+use Language;
+
+/// An expansion returned by `Core::expand_address_detailed`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Expansion {
+    pub value: String,
+    /// Position of this expansion once ranked, `0` being the most canonical one.
+    pub rank: usize,
+    /// Index in `Expansions::roots` of the root this expansion reduces to, i.e. the longest
+    /// root whose tokens appear in this expansion in the same order. `None` if there isn't any.
+    pub root: Option<usize>,
+    /// The dictionary phrases (street types, honorifics, etc) which were matched in the input:
+    /// the runs of tokens of this expansion which were removed from its root, in order.
+    pub phrases: Vec<String>,
+}
+
+impl Expansion {
+    /// Returns `true` if this expansion reduces to one of the root expansions.
+    pub fn is_root(&self) -> bool {
+        self.root.is_some()
+    }
+}
+
+/// The result of `Core::expand_address_detailed`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Expansions {
+    /// Expansions sorted by rank.
+    pub expansions: Vec<Expansion>,
+    /// The root expansions (as returned by `Core::expand_address_root`).
+    pub roots: Vec<String>,
+    /// Languages used for the expansion: the ones from the options if any, otherwise the ones
+    /// detected by the language classifier (if it was set up) with their probability.
+    pub languages: Vec<(Language, f64)>,
+}
+
+impl Expansions {
+    /// Ranks `expansions` (in libpostal order) against their `roots`: expansions reducing to a
+    /// root come first, in the order of their root, then the ones which don't. libpostal order
+    /// is kept otherwise.
+    pub(crate) fn new(
+        expansions: Vec<String>,
+        roots: Vec<String>,
+        languages: Vec<(Language, f64)>,
+    ) -> Expansions {
+        let root_tokens = roots
+            .iter()
+            .map(|r| r.split_whitespace().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let mut expansions = expansions
+            .into_iter()
+            .map(|value| {
+                let (root, phrases) = match_root(&value, &root_tokens);
+                Expansion {
+                    value,
+                    rank: 0,
+                    root,
+                    phrases,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // `sort_by_key` is stable so libpostal order is kept for equal keys.
+        expansions.sort_by_key(|e| e.root.unwrap_or(usize::MAX));
+        for (rank, expansion) in expansions.iter_mut().enumerate() {
+            expansion.rank = rank;
+        }
+        Expansions {
+            expansions,
+            roots,
+            languages,
+        }
+    }
+
+    /// Returns the most canonical expansion.
+    pub fn primary(&self) -> Option<&Expansion> {
+        self.expansions.first()
+    }
+
+    pub fn iter(&self) -> ::std::slice::Iter<'_, Expansion> {
+        self.expansions.iter()
+    }
+}
+
+/// Returns the index of the longest (first on ties) of `roots` which is an ordered subsequence
+/// of the tokens of `expansion`, and the runs of tokens it doesn't contain.
+fn match_root(expansion: &str, roots: &[Vec<&str>]) -> (Option<usize>, Vec<String>) {
+    let tokens = expansion.split_whitespace().collect::<Vec<_>>();
+    let mut best: Option<(usize, Vec<bool>)> = None;
+    let mut best_len = 0;
+    for (index, root) in roots.iter().enumerate() {
+        if root.len() <= best_len {
+            continue;
+        }
+        if let Some(matched) = subsequence(&tokens, root) {
+            best = Some((index, matched));
+            best_len = root.len();
+        }
+    }
+    let (index, matched) = match best {
+        Some(best) => best,
+        None => return (None, Vec::new()),
+    };
+
+    let mut phrases = Vec::new();
+    let mut phrase = Vec::new();
+    for (token, matched) in tokens.iter().zip(matched) {
+        if !matched {
+            phrase.push(*token);
+        } else if !phrase.is_empty() {
+            phrases.push(phrase.join(" "));
+            phrase.clear();
+        }
+    }
+    if !phrase.is_empty() {
+        phrases.push(phrase.join(" "));
+    }
+    (Some(index), phrases)
+}
+
+/// Greedily matches `needle` as a subsequence of `tokens`, returning which tokens were used.
+fn subsequence(tokens: &[&str], needle: &[&str]) -> Option<Vec<bool>> {
+    let mut matched = vec![false; tokens.len()];
+    let mut needle = needle.iter().peekable();
+    for (token, matched) in tokens.iter().zip(matched.iter_mut()) {
+        if needle.peek() == Some(&token) {
+            *matched = true;
+            needle.next();
+        }
+    }
+    if needle.peek().is_none() {
+        Some(matched)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(s: &[&str]) -> Vec<String> {
+        s.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn phrases_are_the_tokens_missing_from_the_root() {
+        let roots = vec![vec!["john"]];
+        assert_eq!(
+            match_root("saint john street", &roots),
+            (Some(0), strings(&["saint", "street"]))
+        );
+        assert_eq!(
+            match_root("saint john street west", &roots),
+            (Some(0), strings(&["saint", "street west"]))
+        );
+        assert_eq!(match_root("john", &roots), (Some(0), Vec::new()));
+        assert_eq!(match_root("mary street", &roots), (None, Vec::new()));
+    }
+
+    #[test]
+    fn longest_root_wins() {
+        let roots = vec![vec!["main"], vec!["100", "main"], vec!["100", "maine"]];
+        assert_eq!(
+            match_root("100 main street", &roots),
+            (Some(1), strings(&["street"]))
+        );
+    }
+
+    #[test]
+    fn expansions_are_ranked_by_root() {
+        let expansions = Expansions::new(
+            strings(&["100 main st", "100 main street", "100 main saint"]),
+            strings(&["100 main saint", "100 main"]),
+            Vec::new(),
+        );
+        let ranked = expansions
+            .iter()
+            .map(|e| (e.value.as_str(), e.rank, e.root))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranked,
+            [
+                ("100 main saint", 0, Some(0)),
+                ("100 main st", 1, Some(1)),
+                ("100 main street", 2, Some(1)),
+            ]
+        );
+        assert_eq!(expansions.primary().unwrap().value, "100 main saint");
+    }
+
+    #[test]
+    fn unmatched_expansions_come_last() {
+        let expansions = Expansions::new(
+            strings(&["foo", "100 main street"]),
+            strings(&["100 main"]),
+            Vec::new(),
+        );
+        assert_eq!(expansions.expansions[0].value, "100 main street");
+        assert!(!expansions.expansions[1].is_root());
+        assert!(expansions.expansions[1].phrases.is_empty());
+    }
+}
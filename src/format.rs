@@ -0,0 +1,468 @@
+//! Renders parsed addresses back into postal labels, using the templates of the
+//! [OpenCage address-formatting](https://github.com/OpenCageData/address-formatting) project.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use regex::Regex;
+use serde_yaml::{Mapping, Value};
+
+use Country;
+use ParsedAddress;
+
+static BUNDLED_TEMPLATES: &str = include_str!("../templates/worldwide.yaml");
+
+#[derive(Debug)]
+pub enum FormatError {
+    Io(io::Error),
+    Yaml(serde_yaml::Error),
+    Regex(regex::Error),
+    /// The templates file is valid YAML but doesn't follow the expected layout.
+    Invalid(String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FormatError::Io(ref e) => write!(f, "failed to read templates: {}", e),
+            FormatError::Yaml(ref e) => write!(f, "failed to parse templates: {}", e),
+            FormatError::Regex(ref e) => write!(f, "invalid replacement pattern: {}", e),
+            FormatError::Invalid(ref e) => write!(f, "invalid templates: {}", e),
+        }
+    }
+}
+
+impl Error for FormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            FormatError::Io(ref e) => Some(e),
+            FormatError::Yaml(ref e) => Some(e),
+            FormatError::Regex(ref e) => Some(e),
+            FormatError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for FormatError {
+    fn from(e: io::Error) -> FormatError {
+        FormatError::Io(e)
+    }
+}
+
+impl From<serde_yaml::Error> for FormatError {
+    fn from(e: serde_yaml::Error) -> FormatError {
+        FormatError::Yaml(e)
+    }
+}
+
+impl From<regex::Error> for FormatError {
+    fn from(e: regex::Error) -> FormatError {
+        FormatError::Regex(e)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Replacement {
+    /// `label=value` rules: replaces the whole component if it is equal to `value`.
+    Component {
+        label: String,
+        value: String,
+        to: String,
+    },
+    Regex {
+        from: Regex,
+        to: String,
+    },
+}
+
+#[derive(Clone, Debug, Default)]
+struct Template {
+    address_template: Option<String>,
+    fallback_template: Option<String>,
+    use_country: Option<String>,
+    change_country: Option<String>,
+    add_component: Option<(String, String)>,
+    replace: Vec<Replacement>,
+    postformat_replace: Vec<(Regex, String)>,
+}
+
+/// Formats `ParsedAddress` values following the address conventions of each country.
+#[derive(Clone, Debug)]
+pub struct Formatter {
+    templates: HashMap<String, Template>,
+    default: Template,
+}
+
+impl Formatter {
+    /// Returns a formatter using the templates bundled with this crate. Only the most common
+    /// countries are covered, the others use the default template.
+    pub fn bundled() -> Formatter {
+        Formatter::from_yaml(BUNDLED_TEMPLATES).expect("invalid bundled templates")
+    }
+
+    /// Loads the templates from a `worldwide.yaml` file of the address-formatting project.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Formatter, FormatError> {
+        Formatter::from_yaml(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_yaml(yaml: &str) -> Result<Formatter, FormatError> {
+        let root: Mapping = match serde_yaml::from_str(yaml)? {
+            Value::Mapping(m) => m,
+            _ => return Err(FormatError::Invalid("expected a mapping".to_owned())),
+        };
+        let mut templates = HashMap::new();
+        let mut default = None;
+
+        for (key, value) in root.iter() {
+            let (key, value) = match (key.as_str(), value.as_mapping()) {
+                (Some(k), Some(v)) => (k, v),
+                // Generic templates only exist to be referenced by aliases.
+                _ => continue,
+            };
+            let template = parse_template(value)?;
+            if key == "default" {
+                default = Some(template);
+            } else {
+                templates.insert(key.to_uppercase(), template);
+            }
+        }
+        let default =
+            default.ok_or_else(|| FormatError::Invalid("missing `default` template".to_owned()))?;
+        if default.address_template.is_none() {
+            return Err(FormatError::Invalid(
+                "missing `default.address_template`".to_owned(),
+            ));
+        }
+        Ok(Formatter { templates, default })
+    }
+
+    /// Renders `address` as a multi-line postal label.
+    pub fn format(&self, address: &ParsedAddress, country: Option<Country>) -> String {
+        self.render(address, country).join("\n")
+    }
+
+    /// Renders `address` on a single line, the lines being separated with `", "`.
+    pub fn format_single_line(&self, address: &ParsedAddress, country: Option<Country>) -> String {
+        self.render(address, country).join(", ")
+    }
+
+    fn render(&self, address: &ParsedAddress, country: Option<Country>) -> Vec<String> {
+        let mut components = address
+            .iter()
+            .map(|(label, value)| (label.to_owned(), value.to_owned()))
+            .collect::<HashMap<_, _>>();
+        let entry = country.and_then(|c| self.templates.get(c.code()));
+
+        if let Some(country) = country {
+            components.insert("country_code".to_owned(), country.code().to_lowercase());
+        }
+        if let Some(entry) = entry {
+            if let Some(ref change) = entry.change_country {
+                let state = components.get("state").cloned().unwrap_or_default();
+                components.insert("country".to_owned(), change.replace("$state", &state));
+            }
+            if let Some((ref label, ref value)) = entry.add_component {
+                components.insert(label.clone(), value.clone());
+            }
+        }
+        // `use_country` entries borrow the templates of another country.
+        let template = match entry {
+            Some(e) => match e.use_country {
+                Some(ref other) => self.templates.get(&other.to_uppercase()).unwrap_or(e),
+                None => e,
+            },
+            None => &self.default,
+        };
+
+        for replacement in &template.replace {
+            apply_replacement(replacement, &mut components);
+        }
+
+        let has_minimum = components.contains_key("road") || components.contains_key("postcode");
+        let text = if has_minimum {
+            template.address_template.as_ref()
+        } else {
+            template
+                .fallback_template
+                .as_ref()
+                .or(self.default.fallback_template.as_ref())
+        }
+        .or(template.address_template.as_ref())
+        .or(self.default.address_template.as_ref())
+        .map(|t| render_template(t, &components))
+        .unwrap_or_default();
+
+        let mut text = cleanup(&text).join("\n");
+        for (from, to) in &template.postformat_replace {
+            text = from.replace_all(&text, to.as_str()).into_owned();
+        }
+        cleanup(&text)
+    }
+}
+
+impl Default for Formatter {
+    fn default() -> Formatter {
+        Formatter::bundled()
+    }
+}
+
+fn parse_template(value: &Mapping) -> Result<Template, FormatError> {
+    let get_str = |key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_owned())
+    };
+    let get_pairs = |key: &str| -> Result<Vec<(String, String)>, FormatError> {
+        let list = match value.get(key) {
+            Some(Value::Sequence(list)) => list,
+            Some(_) => return Err(FormatError::Invalid(format!("`{}` must be a list", key))),
+            None => return Ok(Vec::new()),
+        };
+        list.iter()
+            .map(|pair| match pair.as_sequence().map(|p| p.as_slice()) {
+                Some([Value::String(from), Value::String(to)]) => Ok((from.clone(), to.clone())),
+                _ => Err(FormatError::Invalid(format!(
+                    "`{}` entries must be `[from, to]` pairs",
+                    key
+                ))),
+            })
+            .collect()
+    };
+
+    let mut replace = Vec::new();
+    for (from, to) in get_pairs("replace")? {
+        // Plain `label=value` rules only apply to the matching component.
+        let component_rule = from
+            .find('=')
+            .filter(|&pos| from[..pos].chars().all(|c| c.is_alphanumeric() || c == '_'));
+        replace.push(match component_rule {
+            Some(pos) => Replacement::Component {
+                label: from[..pos].to_owned(),
+                value: from[pos + 1..].to_owned(),
+                to,
+            },
+            None => Replacement::Regex {
+                from: Regex::new(&from)?,
+                to,
+            },
+        });
+    }
+    let postformat_replace = get_pairs("postformat_replace")?
+        .into_iter()
+        .map(|(from, to)| Ok((Regex::new(&from)?, to)))
+        .collect::<Result<Vec<_>, FormatError>>()?;
+    let add_component = get_str("add_component").and_then(|s| {
+        s.find('=')
+            .map(|pos| (s[..pos].to_owned(), s[pos + 1..].to_owned()))
+    });
+
+    Ok(Template {
+        address_template: get_str("address_template"),
+        fallback_template: get_str("fallback_template"),
+        use_country: get_str("use_country"),
+        change_country: get_str("change_country"),
+        add_component,
+        replace,
+        postformat_replace,
+    })
+}
+
+fn apply_replacement(replacement: &Replacement, components: &mut HashMap<String, String>) {
+    match *replacement {
+        Replacement::Component {
+            ref label,
+            ref value,
+            ref to,
+        } => {
+            if let Some(v) = components.get_mut(label) {
+                if v == value {
+                    *v = to.clone();
+                }
+            }
+        }
+        Replacement::Regex { ref from, ref to } => {
+            for v in components.values_mut() {
+                *v = from.replace_all(v, to.as_str()).into_owned();
+            }
+        }
+    }
+}
+
+// Renders the subset of mustache used by the templates: `{{{label}}}` variables and
+// `{{#first}} a || b {{/first}}` sections, which keep the first non-empty alternative.
+fn render_template(template: &str, components: &HashMap<String, String>) -> String {
+    const FIRST_START: &str = "{{#first}}";
+    const FIRST_END: &str = "{{/first}}";
+
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find(FIRST_START) {
+        out.push_str(&render_variables(&rest[..start], components));
+        let after = &rest[start + FIRST_START.len()..];
+        let end = after.find(FIRST_END).unwrap_or(after.len());
+        let chosen = after[..end]
+            .split("||")
+            .map(|alternative| render_variables(alternative, components))
+            .find(|alternative| !alternative.trim().is_empty())
+            .unwrap_or_default();
+        out.push_str(chosen.trim());
+        rest = after.get(end + FIRST_END.len()..).unwrap_or("");
+    }
+    out.push_str(&render_variables(rest, components));
+    out
+}
+
+fn render_variables(text: &str, components: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 3..];
+        match after.find("}}}") {
+            Some(end) => {
+                if let Some(value) = components.get(after[..end].trim()) {
+                    out.push_str(value);
+                }
+                rest = &after[end + 3..];
+            }
+            None => {
+                rest = after;
+                break;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// Removes empty lines, duplicated lines, repeated spaces and dangling commas.
+fn cleanup(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        let mut cleaned = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        while cleaned.contains(", ,") {
+            cleaned = cleaned.replace(", ,", ",");
+        }
+        let cleaned = cleaned
+            .replace(" ,", ",")
+            .trim_matches(|c: char| c == ',' || c.is_whitespace())
+            .to_owned();
+        if cleaned.is_empty() || lines.last() == Some(&cleaned) {
+            continue;
+        }
+        lines.push(cleaned);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(components: &[(&str, &str)]) -> ParsedAddress {
+        let mut address = ParsedAddress::new();
+        for &(label, value) in components {
+            assert!(address.set(label, Some(value)));
+        }
+        address
+    }
+
+    fn format(components: &[(&str, &str)], country: &str) -> String {
+        Formatter::bundled().format(&address(components), Some(country.parse().unwrap()))
+    }
+
+    #[test]
+    fn house_number_order() {
+        let components = [
+            ("house_number", "12"),
+            ("road", "main street"),
+            ("postcode", "10115"),
+            ("city", "berlin"),
+        ];
+        assert_eq!(format(&components, "DE"), "main street 12\n10115 berlin");
+        assert_eq!(format(&components, "FR"), "12 main street\n10115 berlin");
+    }
+
+    #[test]
+    fn unit_details() {
+        let components = [
+            ("house_number", "12"),
+            ("road", "hauptstraße"),
+            ("entrance", "eingang b"),
+            ("level", "3. og"),
+            ("unit", "wohnung 7"),
+            ("postcode", "10115"),
+            ("city", "berlin"),
+        ];
+        assert_eq!(
+            format(&components, "DE"),
+            "hauptstraße 12\neingang b 3. og wohnung 7\n10115 berlin"
+        );
+
+        let components = [
+            ("house_number", "12"),
+            ("road", "rue de rivoli"),
+            ("unit", "appartement 5"),
+            ("level", "étage 2"),
+            ("staircase", "escalier a"),
+            ("entrance", "bâtiment c"),
+            ("postcode", "75001"),
+            ("city", "paris"),
+        ];
+        assert_eq!(
+            format(&components, "FR"),
+            "appartement 5 étage 2 escalier a\nbâtiment c\n12 rue de rivoli\n75001 paris"
+        );
+
+        let components = [
+            ("house_number", "10"),
+            ("road", "downing street"),
+            ("unit", "flat 4"),
+            ("city", "london"),
+            ("postcode", "sw1a 2aa"),
+        ];
+        assert_eq!(
+            format(&components, "GB"),
+            "flat 4\n10 downing street\nlondon\nsw1a 2aa"
+        );
+
+        let components = [
+            ("house_number", "500"),
+            ("road", "main st"),
+            ("unit", "ste 200"),
+            ("city", "springfield"),
+            ("state", "il"),
+            ("postcode", "62701"),
+        ];
+        assert_eq!(
+            format(&components, "US"),
+            "500 main st ste 200\nspringfield, il 62701"
+        );
+    }
+
+    #[test]
+    fn po_box() {
+        let components = [
+            ("po_box", "po box 123"),
+            ("city", "springfield"),
+            ("state", "il"),
+            ("postcode", "62701"),
+        ];
+        assert_eq!(
+            format(&components, "US"),
+            "po box 123\nspringfield, il 62701"
+        );
+        assert_eq!(
+            Formatter::bundled().format_single_line(&address(&components[..2]), None),
+            "po box 123, springfield"
+        );
+    }
+}
@@ -0,0 +1,383 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A language supported by libpostal, identified by its ISO 639-1 code.
+///
+/// Codes libpostal knows about but which aren't listed here can still be used through
+/// `Language::Unknown`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Language {
+    Afrikaans,
+    Arabic,
+    Azerbaijani,
+    Belarusian,
+    Bulgarian,
+    Bengali,
+    Breton,
+    Bosnian,
+    Catalan,
+    Czech,
+    Welsh,
+    Danish,
+    German,
+    Greek,
+    English,
+    Spanish,
+    Estonian,
+    Basque,
+    Persian,
+    Finnish,
+    French,
+    Irish,
+    ScottishGaelic,
+    Galician,
+    Hebrew,
+    Hindi,
+    Croatian,
+    Hungarian,
+    Armenian,
+    Indonesian,
+    Icelandic,
+    Italian,
+    Japanese,
+    Georgian,
+    Kazakh,
+    Korean,
+    Kurdish,
+    Latin,
+    Luxembourgish,
+    Lithuanian,
+    Latvian,
+    Malagasy,
+    Macedonian,
+    Malay,
+    Maltese,
+    Burmese,
+    NorwegianBokmal,
+    Dutch,
+    NorwegianNynorsk,
+    Occitan,
+    Polish,
+    Portuguese,
+    Romanian,
+    Russian,
+    Slovak,
+    Slovenian,
+    Albanian,
+    Serbian,
+    Swedish,
+    Swahili,
+    Tamil,
+    Telugu,
+    Thai,
+    Tagalog,
+    Turkish,
+    Ukrainian,
+    Urdu,
+    Uzbek,
+    Vietnamese,
+    Chinese,
+    Unknown(String),
+}
+
+static KNOWN_LANGUAGES: &[Language] = &[
+    Language::Afrikaans,
+    Language::Arabic,
+    Language::Azerbaijani,
+    Language::Belarusian,
+    Language::Bulgarian,
+    Language::Bengali,
+    Language::Breton,
+    Language::Bosnian,
+    Language::Catalan,
+    Language::Czech,
+    Language::Welsh,
+    Language::Danish,
+    Language::German,
+    Language::Greek,
+    Language::English,
+    Language::Spanish,
+    Language::Estonian,
+    Language::Basque,
+    Language::Persian,
+    Language::Finnish,
+    Language::French,
+    Language::Irish,
+    Language::ScottishGaelic,
+    Language::Galician,
+    Language::Hebrew,
+    Language::Hindi,
+    Language::Croatian,
+    Language::Hungarian,
+    Language::Armenian,
+    Language::Indonesian,
+    Language::Icelandic,
+    Language::Italian,
+    Language::Japanese,
+    Language::Georgian,
+    Language::Kazakh,
+    Language::Korean,
+    Language::Kurdish,
+    Language::Latin,
+    Language::Luxembourgish,
+    Language::Lithuanian,
+    Language::Latvian,
+    Language::Malagasy,
+    Language::Macedonian,
+    Language::Malay,
+    Language::Maltese,
+    Language::Burmese,
+    Language::NorwegianBokmal,
+    Language::Dutch,
+    Language::NorwegianNynorsk,
+    Language::Occitan,
+    Language::Polish,
+    Language::Portuguese,
+    Language::Romanian,
+    Language::Russian,
+    Language::Slovak,
+    Language::Slovenian,
+    Language::Albanian,
+    Language::Serbian,
+    Language::Swedish,
+    Language::Swahili,
+    Language::Tamil,
+    Language::Telugu,
+    Language::Thai,
+    Language::Tagalog,
+    Language::Turkish,
+    Language::Ukrainian,
+    Language::Urdu,
+    Language::Uzbek,
+    Language::Vietnamese,
+    Language::Chinese,
+];
+
+impl Language {
+    /// Returns the language matching `code`, or `Language::Unknown` if there is none.
+    pub fn new(code: &str) -> Language {
+        code.parse()
+            .unwrap_or_else(|_| Language::Unknown(code.to_lowercase()))
+    }
+
+    /// Parses every code of `codes`, failing on the first unknown one.
+    pub fn parse_all<S: AsRef<str>>(codes: &[S]) -> Result<Vec<Language>, UnknownLanguage> {
+        codes.iter().map(|code| code.as_ref().parse()).collect()
+    }
+
+    /// Returns all the languages known by this crate (so all variants but `Unknown`).
+    pub fn all() -> &'static [Language] {
+        KNOWN_LANGUAGES
+    }
+
+    pub fn code(&self) -> &str {
+        match *self {
+            Language::Afrikaans => "af",
+            Language::Arabic => "ar",
+            Language::Azerbaijani => "az",
+            Language::Belarusian => "be",
+            Language::Bulgarian => "bg",
+            Language::Bengali => "bn",
+            Language::Breton => "br",
+            Language::Bosnian => "bs",
+            Language::Catalan => "ca",
+            Language::Czech => "cs",
+            Language::Welsh => "cy",
+            Language::Danish => "da",
+            Language::German => "de",
+            Language::Greek => "el",
+            Language::English => "en",
+            Language::Spanish => "es",
+            Language::Estonian => "et",
+            Language::Basque => "eu",
+            Language::Persian => "fa",
+            Language::Finnish => "fi",
+            Language::French => "fr",
+            Language::Irish => "ga",
+            Language::ScottishGaelic => "gd",
+            Language::Galician => "gl",
+            Language::Hebrew => "he",
+            Language::Hindi => "hi",
+            Language::Croatian => "hr",
+            Language::Hungarian => "hu",
+            Language::Armenian => "hy",
+            Language::Indonesian => "id",
+            Language::Icelandic => "is",
+            Language::Italian => "it",
+            Language::Japanese => "ja",
+            Language::Georgian => "ka",
+            Language::Kazakh => "kk",
+            Language::Korean => "ko",
+            Language::Kurdish => "ku",
+            Language::Latin => "la",
+            Language::Luxembourgish => "lb",
+            Language::Lithuanian => "lt",
+            Language::Latvian => "lv",
+            Language::Malagasy => "mg",
+            Language::Macedonian => "mk",
+            Language::Malay => "ms",
+            Language::Maltese => "mt",
+            Language::Burmese => "my",
+            Language::NorwegianBokmal => "nb",
+            Language::Dutch => "nl",
+            Language::NorwegianNynorsk => "nn",
+            Language::Occitan => "oc",
+            Language::Polish => "pl",
+            Language::Portuguese => "pt",
+            Language::Romanian => "ro",
+            Language::Russian => "ru",
+            Language::Slovak => "sk",
+            Language::Slovenian => "sl",
+            Language::Albanian => "sq",
+            Language::Serbian => "sr",
+            Language::Swedish => "sv",
+            Language::Swahili => "sw",
+            Language::Tamil => "ta",
+            Language::Telugu => "te",
+            Language::Thai => "th",
+            Language::Tagalog => "tl",
+            Language::Turkish => "tr",
+            Language::Ukrainian => "uk",
+            Language::Urdu => "ur",
+            Language::Uzbek => "uz",
+            Language::Vietnamese => "vi",
+            Language::Chinese => "zh",
+            Language::Unknown(ref s) => s,
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(*self, Language::Unknown(_))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownLanguage(pub String);
+
+impl fmt::Display for UnknownLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown language code {:?}", self.0)
+    }
+}
+
+impl Error for UnknownLanguage {}
+
+impl FromStr for Language {
+    type Err = UnknownLanguage;
+
+    /// Only accepts the codes of the known languages (case-insensitive). Use `Language::new` to
+    /// get `Language::Unknown` instead of an error.
+    fn from_str(s: &str) -> Result<Language, UnknownLanguage> {
+        let code = s.trim().to_lowercase();
+        KNOWN_LANGUAGES
+            .iter()
+            .find(|l| l.code() == code)
+            .cloned()
+            .ok_or_else(|| UnknownLanguage(s.to_owned()))
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl AsRef<str> for Language {
+    fn as_ref(&self) -> &str {
+        self.code()
+    }
+}
+
+/// Same as `str::parse`.
+impl<'a> TryFrom<&'a str> for Language {
+    type Error = UnknownLanguage;
+
+    fn try_from(code: &'a str) -> Result<Language, UnknownLanguage> {
+        code.parse()
+    }
+}
+
+/// Same as `str::parse`.
+impl TryFrom<String> for Language {
+    type Error = UnknownLanguage;
+
+    fn try_from(code: String) -> Result<Language, UnknownLanguage> {
+        code.parse()
+    }
+}
+
+/// `Language::Unknown` is written as its raw code.
+#[cfg(feature = "serde")]
+impl Serialize for Language {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+/// Same as `str::parse`, so that typos in options are reported. Unknown codes written by
+/// `Serialize` can be read back with `Language::new`.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Language {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Language, D::Error> {
+        use serde::de::Error;
+
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_all_stops_at_the_first_unknown_code() {
+        assert_eq!(
+            Language::parse_all(&["fr", "de"]),
+            Ok(vec![Language::French, Language::German])
+        );
+        assert_eq!(
+            Language::parse_all(&["fr", "fra", "xx"]),
+            Err(UnknownLanguage("fra".to_owned()))
+        );
+    }
+
+    #[test]
+    fn only_known_codes_convert() {
+        assert_eq!(Language::try_from("fr"), Ok(Language::French));
+        assert_eq!(Language::try_from(" EN ".to_owned()), Ok(Language::English));
+        assert_eq!(
+            Language::try_from("xx"),
+            Err(UnknownLanguage("xx".to_owned()))
+        );
+        assert_eq!(Language::new("XX"), Language::Unknown("xx".to_owned()));
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for language in Language::all() {
+            assert_eq!(language.code().parse().as_ref(), Ok(language));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_is_symmetric() {
+        extern crate serde_json;
+
+        for language in Language::all() {
+            let json = serde_json::to_string(language).unwrap();
+            assert_eq!(&serde_json::from_str::<Language>(&json).unwrap(), language);
+        }
+        let json = serde_json::to_string(&Language::new("xx")).unwrap();
+        assert_eq!(json, "\"xx\"");
+        assert!(serde_json::from_str::<Language>(&json).is_err());
+    }
+}
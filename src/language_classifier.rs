@@ -1,9 +1,12 @@
-use std::ffi::CString;
+use std::ffi::{CStr, CString, NulError};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
+use libc::{c_char, c_double, size_t};
+use options::validate_latlon;
 use sys;
 use traits::{ToC, ToRust};
+use utils::ptr_to_rust;
 
 use Address;
 use Core;
@@ -11,11 +14,57 @@ use DuplicateOptions;
 use DuplicateStatus;
 use FuzzyDuplicateOptions;
 use FuzzyDuplicateStatus;
+use Language;
+use NearDupeHashError;
 use NearDupeHashOptions;
+use NearDupeRecord;
+use UnknownLanguage;
 
 static INIT_LANGUAGE_CLASSIFIER: once_cell::sync::Lazy<Arc<Mutex<(usize, Option<CString>)>>> =
     once_cell::sync::Lazy::new(|| Arc::new(Mutex::new((0, None))));
 
+/// Generates the `&str`, `&[u8]` and `&CStr` versions of a duplicate check.
+macro_rules! duplicate_checks {
+    ($($f:ident => { $name:ident, $bytes:ident, $cstr:ident, })+) => {
+        $(
+            pub fn $name(
+                &self,
+                value1: &str,
+                value2: &str,
+                options: &DuplicateOptions,
+            ) -> DuplicateStatus {
+                self.$cstr(&value1.to_c(), &value2.to_c(), options)
+            }
+
+            #[doc = concat!(
+                "Same as `", stringify!($name), "` but takes the values as bytes, which don't ",
+                "have to be valid UTF-8. Fails if one of them contains a NUL byte.",
+            )]
+            pub fn $bytes(
+                &self,
+                value1: &[u8],
+                value2: &[u8],
+                options: &DuplicateOptions,
+            ) -> Result<DuplicateStatus, NulError> {
+                Ok(self.$cstr(&CString::new(value1)?, &CString::new(value2)?, options))
+            }
+
+            #[doc = concat!("Same as `", stringify!($bytes), "` but takes C strings.")]
+            pub fn $cstr(
+                &self,
+                value1: &CStr,
+                value2: &CStr,
+                options: &DuplicateOptions,
+            ) -> DuplicateStatus {
+                // `_languages` owns the strings `options` points to.
+                let (_languages, _language_ptrs, options) = options.to_c();
+
+                unsafe { sys::$f(value1.as_ptr(), value2.as_ptr(), options) }.to_rust()
+            }
+        )+
+    };
+}
+
 pub struct LanguageClassifier<'a> {
     #[allow(dead_code)]
     inner: &'a Core,
@@ -69,10 +118,25 @@ impl<'a> LanguageClassifier<'a> {
         None
     }
 
+    pub(crate) fn is_initialized() -> bool {
+        INIT_LANGUAGE_CLASSIFIER
+            .lock()
+            .map(|x| x.0 > 0)
+            .unwrap_or(false)
+    }
+
+    /// Returns the languages detected in `address`, along with their probability, most likely
+    /// first.
+    pub fn classify_language(&self, address: &str) -> Vec<(Language, f64)> {
+        classify_language(address)
+    }
+
     pub fn get_near_dupe_hash_default_options(&self) -> NearDupeHashOptions {
         unsafe { sys::libpostal_get_near_dupe_hash_default_options() }.to_rust()
     }
 
+    /// Computes the near-dupe hashes of `addresses`, without coordinates (`options.with_latlon`
+    /// is ignored): use `near_dupe_hashes_for` to give some.
     pub fn near_dupe_hashes(
         &self,
         addresses: &[Address],
@@ -104,15 +168,17 @@ impl<'a> LanguageClassifier<'a> {
         }
     }
 
+    /// Same as `near_dupe_hashes` with the given languages.
     pub fn near_dupe_hashes_languages(
         &self,
         addresses: &[Address],
         options: &NearDupeHashOptions,
-        languages: &[String],
+        languages: &[Language],
     ) -> Vec<String> {
         let (labels, values) = addresses.to_c();
         let mut num_hashes = 0;
-        let (_, languages) = languages.to_c();
+        // `_languages` owns the strings `languages` points to.
+        let (_languages, languages) = languages.to_c();
 
         let ptr = unsafe {
             sys::libpostal_near_dupe_hashes_languages(
@@ -139,12 +205,85 @@ impl<'a> LanguageClassifier<'a> {
         }
     }
 
+    /// Computes the near-dupe hashes of `record`, using its own coordinates when
+    /// `options.with_latlon` is set.
+    ///
+    /// If `record` has no coordinates, hashes are computed without them.
+    pub fn near_dupe_hashes_for(
+        &self,
+        record: &NearDupeRecord,
+        options: &NearDupeHashOptions,
+    ) -> Result<Vec<String>, NearDupeHashError> {
+        let latlon = record.latlon.filter(|_| options.with_latlon);
+        if let Some((latitude, longitude)) = latlon {
+            validate_latlon(latitude, longitude)?;
+            options.validate()?;
+        }
+        let c_options = options.to_c_at(latlon);
+
+        let (labels, values) = record.addresses.to_c();
+        let mut num_hashes = 0;
+        let ptr = if record.languages.is_empty() {
+            unsafe {
+                sys::libpostal_near_dupe_hashes(
+                    record.addresses.len(),
+                    labels.as_ptr(),
+                    values.as_ptr(),
+                    c_options,
+                    &mut num_hashes,
+                )
+            }
+        } else {
+            // `_languages` owns the strings `languages` points to.
+            let (_languages, languages) = record.languages.to_c();
+            unsafe {
+                sys::libpostal_near_dupe_hashes_languages(
+                    record.addresses.len(),
+                    labels.as_ptr(),
+                    values.as_ptr(),
+                    c_options,
+                    languages.len(),
+                    languages.as_ptr(),
+                    &mut num_hashes,
+                )
+            }
+        };
+        let ret = ptr_to_rust(ptr, num_hashes);
+        if !ptr.is_null() {
+            unsafe {
+                sys::libpostal_expansion_array_destroy(ptr, num_hashes);
+            }
+        }
+        Ok(ret)
+    }
+
     pub fn get_default_duplicate_options(&self) -> DuplicateOptions {
         unsafe { sys::libpostal_get_default_duplicate_options() }.to_rust()
     }
 
-    pub fn get_duplicate_options_with_languages(&self, languages: &[String]) -> DuplicateOptions {
-        let (_, languages) = languages.to_c();
+    /// Same as `near_dupe_hashes_languages`, from the language codes.
+    pub fn near_dupe_hashes_language_codes(
+        &self,
+        addresses: &[Address],
+        options: &NearDupeHashOptions,
+        languages: &[&str],
+    ) -> Result<Vec<String>, UnknownLanguage> {
+        let languages = Language::parse_all(languages)?;
+        Ok(self.near_dupe_hashes_languages(addresses, options, &languages))
+    }
+
+    /// Same as `get_duplicate_options_with_languages`, from the language codes.
+    pub fn get_duplicate_options_with_language_codes(
+        &self,
+        languages: &[&str],
+    ) -> Result<DuplicateOptions, UnknownLanguage> {
+        let languages = Language::parse_all(languages)?;
+        Ok(self.get_duplicate_options_with_languages(&languages))
+    }
+
+    pub fn get_duplicate_options_with_languages(&self, languages: &[Language]) -> DuplicateOptions {
+        // `_languages` owns the strings `languages` points to.
+        let (_languages, languages) = languages.to_c();
         unsafe {
             sys::libpostal_get_duplicate_options_with_languages(
                 languages.len() as _,
@@ -162,7 +301,8 @@ impl<'a> LanguageClassifier<'a> {
     ) -> DuplicateStatus {
         let (labels1, values1) = addresses1.to_c();
         let (labels2, values2) = addresses2.to_c();
-        let (_, _, options) = options.to_c();
+        // `_languages` owns the strings `options` points to.
+        let (_languages, _language_ptrs, options) = options.to_c();
 
         unsafe {
             sys::libpostal_is_toponym_duplicate(
@@ -178,106 +318,42 @@ impl<'a> LanguageClassifier<'a> {
         .to_rust()
     }
 
-    pub fn is_name_duplicate(
-        &self,
-        value1: &str,
-        value2: &str,
-        options: &DuplicateOptions,
-    ) -> DuplicateStatus {
-        let value1 = value1.to_c();
-        let value2 = value2.to_c();
-        let (_, _, options) = options.to_c();
-
-        unsafe { sys::libpostal_is_name_duplicate(value1.as_ptr(), value2.as_ptr(), options) }
-            .to_rust()
-    }
-
-    pub fn is_street_duplicate(
-        &self,
-        value1: &str,
-        value2: &str,
-        options: &DuplicateOptions,
-    ) -> DuplicateStatus {
-        let value1 = value1.to_c();
-        let value2 = value2.to_c();
-        let (_, _, options) = options.to_c();
-
-        unsafe { sys::libpostal_is_street_duplicate(value1.as_ptr(), value2.as_ptr(), options) }
-            .to_rust()
-    }
-
-    pub fn is_house_number_duplicate(
-        &self,
-        value1: &str,
-        value2: &str,
-        options: &DuplicateOptions,
-    ) -> DuplicateStatus {
-        let value1 = value1.to_c();
-        let value2 = value2.to_c();
-        let (_, _, options) = options.to_c();
-
-        unsafe {
-            sys::libpostal_is_house_number_duplicate(value1.as_ptr(), value2.as_ptr(), options)
+    duplicate_checks! {
+        libpostal_is_name_duplicate => {
+            is_name_duplicate,
+            is_name_duplicate_bytes,
+            is_name_duplicate_cstr,
         }
-        .to_rust()
-    }
-
-    pub fn is_po_box_duplicate(
-        &self,
-        value1: &str,
-        value2: &str,
-        options: &DuplicateOptions,
-    ) -> DuplicateStatus {
-        let value1 = value1.to_c();
-        let value2 = value2.to_c();
-        let (_, _, options) = options.to_c();
-
-        unsafe { sys::libpostal_is_po_box_duplicate(value1.as_ptr(), value2.as_ptr(), options) }
-            .to_rust()
-    }
-
-    pub fn is_unit_duplicate(
-        &self,
-        value1: &str,
-        value2: &str,
-        options: &DuplicateOptions,
-    ) -> DuplicateStatus {
-        let value1 = value1.to_c();
-        let value2 = value2.to_c();
-        let (_, _, options) = options.to_c();
-
-        unsafe { sys::libpostal_is_unit_duplicate(value1.as_ptr(), value2.as_ptr(), options) }
-            .to_rust()
-    }
-
-    pub fn is_floor_duplicate(
-        &self,
-        value1: &str,
-        value2: &str,
-        options: &DuplicateOptions,
-    ) -> DuplicateStatus {
-        let value1 = value1.to_c();
-        let value2 = value2.to_c();
-        let (_, _, options) = options.to_c();
-
-        unsafe { sys::libpostal_is_floor_duplicate(value1.as_ptr(), value2.as_ptr(), options) }
-            .to_rust()
-    }
-
-    pub fn is_postal_code_duplicate(
-        &self,
-        value1: &str,
-        value2: &str,
-        options: &DuplicateOptions,
-    ) -> DuplicateStatus {
-        let value1 = value1.to_c();
-        let value2 = value2.to_c();
-        let (_, _, options) = options.to_c();
-
-        unsafe {
-            sys::libpostal_is_postal_code_duplicate(value1.as_ptr(), value2.as_ptr(), options)
+        libpostal_is_street_duplicate => {
+            is_street_duplicate,
+            is_street_duplicate_bytes,
+            is_street_duplicate_cstr,
+        }
+        libpostal_is_house_number_duplicate => {
+            is_house_number_duplicate,
+            is_house_number_duplicate_bytes,
+            is_house_number_duplicate_cstr,
+        }
+        libpostal_is_po_box_duplicate => {
+            is_po_box_duplicate,
+            is_po_box_duplicate_bytes,
+            is_po_box_duplicate_cstr,
+        }
+        libpostal_is_unit_duplicate => {
+            is_unit_duplicate,
+            is_unit_duplicate_bytes,
+            is_unit_duplicate_cstr,
+        }
+        libpostal_is_floor_duplicate => {
+            is_floor_duplicate,
+            is_floor_duplicate_bytes,
+            is_floor_duplicate_cstr,
+        }
+        libpostal_is_postal_code_duplicate => {
+            is_postal_code_duplicate,
+            is_postal_code_duplicate_bytes,
+            is_postal_code_duplicate_cstr,
         }
-        .to_rust()
     }
 
     pub fn get_default_fuzzy_duplicate_options(&self) -> FuzzyDuplicateOptions {
@@ -286,9 +362,10 @@ impl<'a> LanguageClassifier<'a> {
 
     pub fn get_default_fuzzy_duplicate_options_with_languages(
         &self,
-        languages: &[String],
+        languages: &[Language],
     ) -> FuzzyDuplicateOptions {
-        let (_, languages) = languages.to_c();
+        // `_languages` owns the strings `languages` points to.
+        let (_languages, languages) = languages.to_c();
         unsafe {
             sys::libpostal_get_default_fuzzy_duplicate_options_with_languages(
                 languages.len() as _,
@@ -298,40 +375,113 @@ impl<'a> LanguageClassifier<'a> {
         .to_rust()
     }
 
+    /// Same as `get_default_fuzzy_duplicate_options_with_languages`, from the language codes.
+    pub fn get_default_fuzzy_duplicate_options_with_language_codes(
+        &self,
+        languages: &[&str],
+    ) -> Result<FuzzyDuplicateOptions, UnknownLanguage> {
+        let languages = Language::parse_all(languages)?;
+        Ok(self.get_default_fuzzy_duplicate_options_with_languages(&languages))
+    }
+
     pub fn is_name_duplicate_fuzzy(
         &self,
         values1: &[(String, f64)],
         values2: &[(String, f64)],
         options: &FuzzyDuplicateOptions,
     ) -> FuzzyDuplicateStatus {
-        let converter = |v: &[(String, f64)]| {
-            let mut labels = Vec::with_capacity(v.len());
-            let mut c_labels = Vec::with_capacity(v.len());
-            let mut floats = Vec::with_capacity(v.len());
-
-            for (l, f) in v.iter() {
-                let label = l.to_c();
-                c_labels.push(label.as_ptr());
-                labels.push(label);
-                floats.push(*f);
-            }
-            (labels, c_labels, floats)
-        };
-        let (_, labels1, v1) = converter(values1);
-        let (_, labels2, v2) = converter(values2);
-        let (_, _, options) = options.to_c();
+        is_duplicate_fuzzy(
+            sys::libpostal_is_name_duplicate_fuzzy,
+            values1,
+            values2,
+            options,
+        )
+    }
 
-        unsafe {
-            sys::libpostal_is_name_duplicate_fuzzy(
-                values1.len() as _,
-                labels1.as_ptr(),
-                v1.as_ptr(),
-                values2.len(),
-                labels2.as_ptr(),
-                v2.as_ptr(),
-                options,
-            )
+    pub fn is_street_duplicate_fuzzy(
+        &self,
+        values1: &[(String, f64)],
+        values2: &[(String, f64)],
+        options: &FuzzyDuplicateOptions,
+    ) -> FuzzyDuplicateStatus {
+        is_duplicate_fuzzy(
+            sys::libpostal_is_street_duplicate_fuzzy,
+            values1,
+            values2,
+            options,
+        )
+    }
+}
+
+type FuzzyDuplicateFn = unsafe extern "C" fn(
+    size_t,
+    *const *const c_char,
+    *const c_double,
+    size_t,
+    *const *const c_char,
+    *const c_double,
+    sys::libpostal_fuzzy_duplicate_options_t,
+) -> sys::libpostal_fuzzy_duplicate_status_t;
+
+fn is_duplicate_fuzzy(
+    f: FuzzyDuplicateFn,
+    values1: &[(String, f64)],
+    values2: &[(String, f64)],
+    options: &FuzzyDuplicateOptions,
+) -> FuzzyDuplicateStatus {
+    let converter = |v: &[(String, f64)]| {
+        let mut labels = Vec::with_capacity(v.len());
+        let mut c_labels = Vec::with_capacity(v.len());
+        let mut floats = Vec::with_capacity(v.len());
+
+        for (l, score) in v.iter() {
+            let label = l.to_c();
+            c_labels.push(label.as_ptr());
+            labels.push(label);
+            floats.push(*score);
         }
-        .to_rust()
+        (labels, c_labels, floats)
+    };
+    // The owned strings must outlive the call since only pointers are given to libpostal.
+    let (_labels1, labels1, v1) = converter(values1);
+    let (_labels2, labels2, v2) = converter(values2);
+    let (_languages, _language_ptrs, options) = options.to_c();
+
+    unsafe {
+        f(
+            values1.len() as _,
+            labels1.as_ptr(),
+            v1.as_ptr(),
+            values2.len(),
+            labels2.as_ptr(),
+            v2.as_ptr(),
+            options,
+        )
+    }
+    .to_rust()
+}
+
+pub(crate) fn classify_language(address: &str) -> Vec<(Language, f64)> {
+    let address = address.to_c();
+    let data = unsafe { sys::libpostal_classify_language(address.as_ptr()) };
+    if data.is_null() {
+        return Vec::new();
+    }
+    unsafe {
+        let ret = {
+            let data = &*data;
+            if data.languages.is_null() || data.probs.is_null() {
+                Vec::new()
+            } else {
+                (0..data.num_languages)
+                    .map(|i| {
+                        let language = (*data.languages.add(i)).to_rust();
+                        (Language::new(&language), *data.probs.add(i))
+                    })
+                    .collect()
+            }
+        };
+        sys::libpostal_language_classifier_response_destroy(data);
+        ret
     }
 }
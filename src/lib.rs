@@ -1,21 +1,80 @@
+#[cfg(feature = "arrow")]
+extern crate arrow_array;
+#[cfg(feature = "arrow")]
+extern crate arrow_buffer;
+#[cfg(feature = "arrow")]
+extern crate arrow_schema;
+#[cfg(feature = "encoding")]
+extern crate encoding_rs;
 extern crate libc;
+#[cfg(feature = "parquet")]
+extern crate parquet;
+#[cfg(feature = "format")]
+extern crate regex;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "capi")]
+extern crate serde_json;
+#[cfg(feature = "format")]
+extern crate serde_yaml;
+#[cfg(feature = "tantivy")]
+extern crate tantivy_tokenizer_api;
 
-pub use address::Address;
+pub use address::{Address, NearDupeRecord};
 pub use address_parser::AddressParser;
+pub use clustering::{ClusterBuilder, Clusters, ReviewEdge};
 pub use core::Core;
-pub use enums::{AddressComponent, DuplicateStatus};
+pub use country::{Country, UnknownCountry};
+pub use enums::{AddressComponent, DuplicateStatus, UnknownAddressComponent};
+pub use expansion::{Expansion, Expansions};
+pub use language::{Language, UnknownLanguage};
 pub use language_classifier::LanguageClassifier;
 pub use options::{
-    AddressComponents, AddressParserOptions, DuplicateOptions, FuzzyDuplicateOptions,
-    FuzzyDuplicateStatus, NearDupeHashOptions, NormalizeOptions,
+    AddressComponents, AddressParserOptions, DuplicateOptions, DuplicateOptionsBuilder,
+    FuzzyDuplicateOptions, FuzzyDuplicateOptionsBuilder, FuzzyDuplicateStatus, NearDupeHashError,
+    NearDupeHashOptions, NearDupeHashOptionsBuilder, NormalizeOptions, NormalizeOptionsBuilder,
+    ParseHints, MAX_GEOHASH_PRECISION,
 };
+pub use parse_response::{ParseResponse, ParseResponseIter};
+pub use parsed_address::{component_for_label, ParsedAddress, LABELS};
+pub use quality::{ParseQuality, ParseWarning};
+pub use search_keys::KeyOptions;
+pub use spans::{SpanOptions, SpannedComponent};
+pub use tokenizer::{NormalizedToken, Token, TokenType};
+pub use utf8::LossyConversion;
 
 mod address;
 mod address_parser;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "parquet")]
+pub mod batch;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod clustering;
 mod core;
+mod country;
+#[cfg(feature = "encoding")]
+pub mod encoding;
 mod enums;
+mod expansion;
+#[cfg(feature = "format")]
+pub mod format;
+mod language;
 mod language_classifier;
 mod options;
+mod parse_response;
+mod parsed_address;
+pub mod postcode;
+mod quality;
+mod search_keys;
+mod spans;
+// The C API only exposes `capi`, not the libpostal bindings.
+/// cbindgen:ignore
 pub mod sys;
+#[cfg(feature = "tantivy")]
+pub mod tantivy;
+mod tokenizer;
 mod traits;
+mod utf8;
 mod utils;
@@ -1,19 +1,67 @@
+use std::error::Error;
 use std::ffi::CString;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign};
+use std::str::FromStr;
 
-use enums::{AddressComponent, DuplicateStatus};
+use country::Country;
+use enums::{AddressComponent, DuplicateStatus, UnknownAddressComponent};
+use language::{Language, UnknownLanguage};
 use sys;
 use traits::{ToC, ToRust};
 
 use libc::{c_char, free};
 
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// Generates the fluent setters of the options builders.
+macro_rules! builder_setters {
+    ($($name:ident: $ty:ty,)+) => {
+        $(
+            pub fn $name(mut self, $name: $ty) -> Self {
+                self.inner.$name = $name;
+                self
+            }
+        )+
+    };
+}
+
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct AddressComponents {
     inner: u16,
 }
 
 impl AddressComponents {
     pub fn new() -> AddressComponents {
-        AddressComponents { inner: 0 }
+        AddressComponents::empty()
+    }
+
+    pub fn empty() -> AddressComponents {
+        AddressComponents {
+            inner: sys::LIBPOSTAL_ADDRESS_NONE,
+        }
+    }
+
+    pub fn all() -> AddressComponents {
+        AddressComponents {
+            inner: sys::LIBPOSTAL_ADDRESS_ALL,
+        }
+    }
+
+    /// Creates a set from raw libpostal bits. Unknown (reserved) bits are kept as is.
+    pub fn from_bits(bits: u16) -> AddressComponents {
+        AddressComponents { inner: bits }
+    }
+
+    pub fn bits(&self) -> u16 {
+        self.inner
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner == sys::LIBPOSTAL_ADDRESS_NONE
     }
 
     pub fn add(&mut self, component: AddressComponent) -> &mut AddressComponents {
@@ -27,11 +75,185 @@ impl AddressComponents {
         self
     }
 
+    /// Returns `true` if all the bits of `component` are set.
+    pub fn contains(&self, component: AddressComponent) -> bool {
+        let component = component.to_c();
+        self.inner & component == component
+    }
+
+    pub fn contains_all(&self, other: AddressComponents) -> bool {
+        self.inner & other.inner == other.inner
+    }
+
+    pub fn intersects(&self, other: AddressComponents) -> bool {
+        self.inner & other.inner != 0
+    }
+
+    pub fn union(&self, other: AddressComponents) -> AddressComponents {
+        AddressComponents::from_bits(self.inner | other.inner)
+    }
+
+    pub fn intersection(&self, other: AddressComponents) -> AddressComponents {
+        AddressComponents::from_bits(self.inner & other.inner)
+    }
+
+    pub fn difference(&self, other: AddressComponents) -> AddressComponents {
+        AddressComponents::from_bits(self.inner & !other.inner)
+    }
+
+    /// Returns the reserved bits (unused by libpostal) which are set.
+    pub fn reserved_bits(&self) -> u16 {
+        self.inner & sys::LIBPOSTAL_ADDRESS_RESERVED
+    }
+
+    /// Iterates over the single components which are set, in bit order. `AddressComponent::All`
+    /// is never returned and reserved bits are ignored.
+    pub fn iter(&self) -> impl Iterator<Item = AddressComponent> {
+        let components = *self;
+        AddressComponent::SINGLE
+            .iter()
+            .cloned()
+            .filter(move |c| components.contains(*c))
+    }
+
     pub(crate) fn from_c(value: u16) -> AddressComponents {
         AddressComponents { inner: value }
     }
 }
 
+impl From<AddressComponent> for AddressComponents {
+    fn from(component: AddressComponent) -> AddressComponents {
+        AddressComponents::from_bits(component.to_c())
+    }
+}
+
+impl FromIterator<AddressComponent> for AddressComponents {
+    fn from_iter<I: IntoIterator<Item = AddressComponent>>(iter: I) -> AddressComponents {
+        let mut ret = AddressComponents::empty();
+        ret.extend(iter);
+        ret
+    }
+}
+
+impl Extend<AddressComponent> for AddressComponents {
+    fn extend<I: IntoIterator<Item = AddressComponent>>(&mut self, iter: I) {
+        for component in iter {
+            self.add(component);
+        }
+    }
+}
+
+impl<T: Into<AddressComponents>> BitOr<T> for AddressComponents {
+    type Output = AddressComponents;
+
+    fn bitor(self, other: T) -> AddressComponents {
+        self.union(other.into())
+    }
+}
+
+impl<T: Into<AddressComponents>> BitOrAssign<T> for AddressComponents {
+    fn bitor_assign(&mut self, other: T) {
+        *self = *self | other;
+    }
+}
+
+impl<T: Into<AddressComponents>> BitAnd<T> for AddressComponents {
+    type Output = AddressComponents;
+
+    fn bitand(self, other: T) -> AddressComponents {
+        self.intersection(other.into())
+    }
+}
+
+impl<T: Into<AddressComponents>> BitAndAssign<T> for AddressComponents {
+    fn bitand_assign(&mut self, other: T) {
+        *self = *self & other;
+    }
+}
+
+impl<T: Into<AddressComponents>> Sub<T> for AddressComponents {
+    type Output = AddressComponents;
+
+    fn sub(self, other: T) -> AddressComponents {
+        self.difference(other.into())
+    }
+}
+
+impl<T: Into<AddressComponents>> SubAssign<T> for AddressComponents {
+    fn sub_assign(&mut self, other: T) {
+        *self = *self - other;
+    }
+}
+
+impl Not for AddressComponents {
+    type Output = AddressComponents;
+
+    fn not(self) -> AddressComponents {
+        AddressComponents::from_bits(!self.inner)
+    }
+}
+
+impl<T: Into<AddressComponents>> BitOr<T> for AddressComponent {
+    type Output = AddressComponents;
+
+    fn bitor(self, other: T) -> AddressComponents {
+        AddressComponents::from(self) | other
+    }
+}
+
+impl fmt::Display for AddressComponents {
+    /// Writes the components names separated with `|` (like `street|toponym`), `none` if empty
+    /// or `all` if all bits are set. Reserved bits are written in hexadecimal.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("none");
+        }
+        if self.inner == sys::LIBPOSTAL_ADDRESS_ALL {
+            return f.write_str(AddressComponent::All.name());
+        }
+        let mut first = true;
+        for component in self.iter() {
+            if !first {
+                f.write_str("|")?;
+            }
+            f.write_str(component.name())?;
+            first = false;
+        }
+        if self.reserved_bits() != 0 {
+            if !first {
+                f.write_str("|")?;
+            }
+            write!(f, "{:#x}", self.reserved_bits())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for AddressComponents {
+    type Err = UnknownAddressComponent;
+
+    /// Parses the output of `Display`: component names (case-insensitive) or hexadecimal bits
+    /// separated with `|`.
+    fn from_str(s: &str) -> Result<AddressComponents, UnknownAddressComponent> {
+        let mut ret = AddressComponents::empty();
+
+        for part in s.split('|').map(|p| p.trim()) {
+            if part.eq_ignore_ascii_case("none") {
+                continue;
+            }
+            if part.starts_with("0x") || part.starts_with("0X") {
+                ret |= AddressComponents::from_bits(
+                    u16::from_str_radix(&part[2..], 16)
+                        .map_err(|_| UnknownAddressComponent(part.to_owned()))?,
+                );
+            } else {
+                ret |= part.parse::<AddressComponent>()?;
+            }
+        }
+        Ok(ret)
+    }
+}
+
 impl ToC for AddressComponents {
     type Out = u16;
 
@@ -52,8 +274,9 @@ impl Drop for CStringsWrapper {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
 pub struct NormalizeOptions {
-    pub languages: Vec<String>,
+    pub languages: Vec<Language>,
     pub address_components: AddressComponents,
     pub latin_ascii: bool,
     pub transliterate: bool,
@@ -75,6 +298,110 @@ pub struct NormalizeOptions {
     pub roman_numerals: bool,
 }
 
+impl Default for NormalizeOptions {
+    /// Mirrors `libpostal_get_default_options`.
+    fn default() -> NormalizeOptions {
+        let mut address_components = AddressComponents::new();
+        address_components
+            .add(AddressComponent::Name)
+            .add(AddressComponent::HouseNumber)
+            .add(AddressComponent::Street)
+            .add(AddressComponent::POBox)
+            .add(AddressComponent::Unit)
+            .add(AddressComponent::Level)
+            .add(AddressComponent::Entrance)
+            .add(AddressComponent::Staircase)
+            .add(AddressComponent::PostalCode);
+
+        NormalizeOptions {
+            languages: Vec::new(),
+            address_components,
+            latin_ascii: true,
+            transliterate: true,
+            strip_accents: true,
+            decompose: true,
+            lowercase: true,
+            trim_string: true,
+            drop_parentheticals: true,
+            replace_numeric_hyphens: false,
+            delete_numeric_hyphens: false,
+            split_alpha_from_numeric: true,
+            replace_word_hyphens: true,
+            delete_word_hyphens: true,
+            delete_final_periods: true,
+            delete_acronym_periods: true,
+            drop_english_possessives: true,
+            delete_apostrophes: true,
+            expand_numex: true,
+            roman_numerals: true,
+        }
+    }
+}
+
+impl NormalizeOptions {
+    /// Returns the default options using the default languages of `country`.
+    pub fn for_country(country: Country) -> NormalizeOptions {
+        NormalizeOptions::builder().country(country).build()
+    }
+
+    pub fn builder() -> NormalizeOptionsBuilder {
+        NormalizeOptionsBuilder {
+            inner: NormalizeOptions::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NormalizeOptionsBuilder {
+    inner: NormalizeOptions,
+}
+
+impl NormalizeOptionsBuilder {
+    /// Uses the default languages of `country`.
+    pub fn country(mut self, country: Country) -> Self {
+        self.inner.languages = country.default_languages().to_vec();
+        self
+    }
+
+    /// Use `str::parse` or `Language::try_from` to get the languages from their codes.
+    pub fn languages<I: IntoIterator<Item = Language>>(mut self, languages: I) -> Self {
+        self.inner.languages = languages.into_iter().collect();
+        self
+    }
+
+    /// Same as `languages`, from the language codes.
+    pub fn language_codes(mut self, codes: &[&str]) -> Result<Self, UnknownLanguage> {
+        self.inner.languages = Language::parse_all(codes)?;
+        Ok(self)
+    }
+
+    builder_setters! {
+        address_components: AddressComponents,
+        latin_ascii: bool,
+        transliterate: bool,
+        strip_accents: bool,
+        decompose: bool,
+        lowercase: bool,
+        trim_string: bool,
+        drop_parentheticals: bool,
+        replace_numeric_hyphens: bool,
+        delete_numeric_hyphens: bool,
+        split_alpha_from_numeric: bool,
+        replace_word_hyphens: bool,
+        delete_word_hyphens: bool,
+        delete_final_periods: bool,
+        delete_acronym_periods: bool,
+        drop_english_possessives: bool,
+        delete_apostrophes: bool,
+        expand_numex: bool,
+        roman_numerals: bool,
+    }
+
+    pub fn build(self) -> NormalizeOptions {
+        self.inner
+    }
+}
+
 impl ToC for NormalizeOptions {
     type Out = (CStringsWrapper, sys::libpostal_normalize_options_t);
 
@@ -85,7 +412,7 @@ impl ToC for NormalizeOptions {
             .languages
             .iter()
             .map(|s| {
-                CString::new(s.as_str())
+                CString::new(s.code())
                     .expect("CString::new failed")
                     .into_raw()
             })
@@ -133,7 +460,7 @@ impl ToRust for sys::libpostal_normalize_options_t {
             unsafe {
                 for pos in 0..self.num_languages {
                     let elem = self.languages.offset(pos as _);
-                    languages.push((*elem).to_rust());
+                    languages.push(Language::new(&(*elem).to_rust()));
                     free(*elem as _);
                 }
                 free(self.languages as _);
@@ -168,16 +495,75 @@ impl ToRust for sys::libpostal_normalize_options_t {
 // This type is used mostly to not forget to free CString memory once we're done.
 pub(crate) struct CStringWrapper(*mut c_char);
 
+impl CStringWrapper {
+    fn new(s: Option<&str>) -> CStringWrapper {
+        CStringWrapper(match s {
+            Some(s) => CString::new(s).expect("CString::new failed").into_raw(),
+            None => ::std::ptr::null_mut(),
+        })
+    }
+}
+
 impl Drop for CStringWrapper {
     fn drop(&mut self) {
-        unsafe { CString::from_raw(self.0) };
+        if !self.0.is_null() {
+            let _ = unsafe { CString::from_raw(self.0) };
+        }
     }
 }
 
-#[derive(Clone, Debug)]
+/// Hints given to the address parser. When `None`, libpostal guesses by itself.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ParseHints<'a> {
+    pub language: Option<&'a str>,
+    pub country: Option<&'a str>,
+}
+
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
 pub struct AddressParserOptions {
-    language: String,
-    country: String,
+    language: Option<String>,
+    country: Option<String>,
+}
+
+impl AddressParserOptions {
+    pub fn new() -> AddressParserOptions {
+        AddressParserOptions::default()
+    }
+
+    /// Returns options with `country` as country hint.
+    pub fn for_country(country: Country) -> AddressParserOptions {
+        AddressParserOptions {
+            language: None,
+            country: Some(country.libpostal_code()),
+        }
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_deref()
+    }
+
+    pub fn set_language<S: Into<String>>(&mut self, language: Option<S>) -> &mut Self {
+        self.language = language.map(|s| s.into());
+        self
+    }
+
+    pub fn set_country<S: Into<String>>(&mut self, country: Option<S>) -> &mut Self {
+        self.country = country.map(|s| s.into());
+        self
+    }
+}
+
+impl<'a> From<ParseHints<'a>> for AddressParserOptions {
+    fn from(hints: ParseHints<'a>) -> AddressParserOptions {
+        AddressParserOptions {
+            language: hints.language.map(|s| s.to_owned()),
+            country: hints.country.map(|s| s.to_owned()),
+        }
+    }
 }
 
 impl ToC for AddressParserOptions {
@@ -188,20 +574,14 @@ impl ToC for AddressParserOptions {
 
     #[inline]
     fn to_c(&self) -> Self::Out {
-        let language = CString::new(self.language.as_str())
-            .expect("CString::new failed")
-            .into_raw() as usize;
-        let country = CString::new(self.language.as_str())
-            .expect("CString::new failed")
-            .into_raw() as usize;
+        let language = CStringWrapper::new(self.language());
+        let country = CStringWrapper::new(self.country());
+        let options = sys::libpostal_address_parser_options_t {
+            language: language.0,
+            country: country.0,
+        };
 
-        (
-            (CStringWrapper(language as _), CStringWrapper(country as _)),
-            sys::libpostal_address_parser_options_t {
-                language: language as _,
-                country: country as _,
-            },
-        )
+        ((language, country), options)
     }
 }
 
@@ -210,8 +590,15 @@ impl ToRust for sys::libpostal_address_parser_options_t {
 
     #[inline]
     fn to_rust(&self) -> AddressParserOptions {
-        let language = self.language.to_rust();
-        let country = self.country.to_rust();
+        let to_option = |ptr: *mut c_char| {
+            if ptr.is_null() {
+                None
+            } else {
+                Some(ptr.to_rust())
+            }
+        };
+        let language = to_option(self.language);
+        let country = to_option(self.country);
 
         unsafe {
             free(self.language as _);
@@ -221,7 +608,10 @@ impl ToRust for sys::libpostal_address_parser_options_t {
     }
 }
 
+/// The coordinates used with `with_latlon` aren't options: they're given with each record (see
+/// `NearDupeRecord`).
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
 pub struct NearDupeHashOptions {
     pub with_name: bool,
     pub with_address: bool,
@@ -230,18 +620,130 @@ pub struct NearDupeHashOptions {
     pub with_small_containing_boundaries: bool,
     pub with_postal_code: bool,
     pub with_latlon: bool,
-    pub latitude: f64,
-    pub longitude: f64,
     pub geohash_precision: u32,
     pub name_and_address_keys: bool,
     pub name_only_keys: bool,
     pub address_only_keys: bool,
 }
 
-impl ToC for NearDupeHashOptions {
-    type Out = sys::libpostal_near_dupe_hash_options_t;
+impl Default for NearDupeHashOptions {
+    /// Mirrors `libpostal_get_near_dupe_hash_default_options`.
+    fn default() -> NearDupeHashOptions {
+        NearDupeHashOptions {
+            with_name: true,
+            with_address: true,
+            with_unit: false,
+            with_city_or_equivalent: true,
+            with_small_containing_boundaries: true,
+            with_postal_code: true,
+            with_latlon: false,
+            geohash_precision: 6,
+            name_and_address_keys: true,
+            name_only_keys: false,
+            address_only_keys: false,
+        }
+    }
+}
 
-    fn to_c(&self) -> Self::Out {
+impl NearDupeHashOptions {
+    pub fn builder() -> NearDupeHashOptionsBuilder {
+        NearDupeHashOptionsBuilder {
+            inner: NearDupeHashOptions::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NearDupeHashOptionsBuilder {
+    inner: NearDupeHashOptions,
+}
+
+impl NearDupeHashOptionsBuilder {
+    builder_setters! {
+        with_name: bool,
+        with_address: bool,
+        with_unit: bool,
+        with_city_or_equivalent: bool,
+        with_small_containing_boundaries: bool,
+        with_postal_code: bool,
+        with_latlon: bool,
+        geohash_precision: u32,
+        name_and_address_keys: bool,
+        name_only_keys: bool,
+        address_only_keys: bool,
+    }
+
+    pub fn build(self) -> NearDupeHashOptions {
+        self.inner
+    }
+}
+
+/// Maximum geohash precision supported by libpostal.
+pub const MAX_GEOHASH_PRECISION: u32 = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NearDupeHashError {
+    InvalidLatitude(f64),
+    InvalidLongitude(f64),
+    InvalidGeohashPrecision(u32),
+}
+
+impl fmt::Display for NearDupeHashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NearDupeHashError::InvalidLatitude(x) => {
+                write!(f, "invalid latitude {}: expected a value in [-90, 90]", x)
+            }
+            NearDupeHashError::InvalidLongitude(x) => {
+                write!(
+                    f,
+                    "invalid longitude {}: expected a value in [-180, 180]",
+                    x
+                )
+            }
+            NearDupeHashError::InvalidGeohashPrecision(x) => write!(
+                f,
+                "invalid geohash precision {}: expected a value in [1, {}]",
+                x, MAX_GEOHASH_PRECISION
+            ),
+        }
+    }
+}
+
+impl Error for NearDupeHashError {}
+
+impl NearDupeHashOptions {
+    /// Checks that the geohash precision makes sense when `with_latlon` is set.
+    pub fn validate(&self) -> Result<(), NearDupeHashError> {
+        if !self.with_latlon {
+            return Ok(());
+        }
+        if self.geohash_precision == 0 || self.geohash_precision > MAX_GEOHASH_PRECISION {
+            return Err(NearDupeHashError::InvalidGeohashPrecision(
+                self.geohash_precision,
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn validate_latlon(latitude: f64, longitude: f64) -> Result<(), NearDupeHashError> {
+    if !latitude.is_finite() || !(-90. ..=90.).contains(&latitude) {
+        return Err(NearDupeHashError::InvalidLatitude(latitude));
+    }
+    if !longitude.is_finite() || !(-180. ..=180.).contains(&longitude) {
+        return Err(NearDupeHashError::InvalidLongitude(longitude));
+    }
+    Ok(())
+}
+
+impl NearDupeHashOptions {
+    /// `with_latlon` is only kept if there are coordinates.
+    pub(crate) fn to_c_at(
+        &self,
+        latlon: Option<(f64, f64)>,
+    ) -> sys::libpostal_near_dupe_hash_options_t {
+        let (latitude, longitude) = latlon.unwrap_or_default();
         sys::libpostal_near_dupe_hash_options_t {
             with_name: self.with_name.to_c(),
             with_address: self.with_address.to_c(),
@@ -249,9 +751,9 @@ impl ToC for NearDupeHashOptions {
             with_city_or_equivalent: self.with_city_or_equivalent.to_c(),
             with_small_containing_boundaries: self.with_small_containing_boundaries.to_c(),
             with_postal_code: self.with_postal_code.to_c(),
-            with_latlon: self.with_latlon.to_c(),
-            latitude: self.latitude,
-            longitude: self.longitude,
+            with_latlon: (self.with_latlon && latlon.is_some()).to_c(),
+            latitude,
+            longitude,
             geohash_precision: self.geohash_precision,
             name_and_address_keys: self.name_and_address_keys.to_c(),
             name_only_keys: self.name_only_keys.to_c(),
@@ -260,6 +762,15 @@ impl ToC for NearDupeHashOptions {
     }
 }
 
+/// Without coordinates, so `with_latlon` is ignored.
+impl ToC for NearDupeHashOptions {
+    type Out = sys::libpostal_near_dupe_hash_options_t;
+
+    fn to_c(&self) -> Self::Out {
+        self.to_c_at(None)
+    }
+}
+
 impl ToRust for sys::libpostal_near_dupe_hash_options_t {
     type Out = NearDupeHashOptions;
 
@@ -273,8 +784,6 @@ impl ToRust for sys::libpostal_near_dupe_hash_options_t {
             with_small_containing_boundaries: self.with_small_containing_boundaries.to_rust(),
             with_postal_code: self.with_postal_code.to_rust(),
             with_latlon: self.with_latlon.to_rust(),
-            latitude: self.latitude,
-            longitude: self.longitude,
             geohash_precision: self.geohash_precision,
             name_and_address_keys: self.name_and_address_keys.to_rust(),
             name_only_keys: self.name_only_keys.to_rust(),
@@ -284,8 +793,60 @@ impl ToRust for sys::libpostal_near_dupe_hash_options_t {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
 pub struct DuplicateOptions {
-    pub languages: Vec<String>,
+    pub languages: Vec<Language>,
+}
+
+impl Default for DuplicateOptions {
+    /// Mirrors `libpostal_get_default_duplicate_options`.
+    fn default() -> DuplicateOptions {
+        DuplicateOptions {
+            languages: Vec::new(),
+        }
+    }
+}
+
+impl DuplicateOptions {
+    /// Returns the default options using the default languages of `country`.
+    pub fn for_country(country: Country) -> DuplicateOptions {
+        DuplicateOptions::builder().country(country).build()
+    }
+
+    pub fn builder() -> DuplicateOptionsBuilder {
+        DuplicateOptionsBuilder {
+            inner: DuplicateOptions::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DuplicateOptionsBuilder {
+    inner: DuplicateOptions,
+}
+
+impl DuplicateOptionsBuilder {
+    /// Uses the default languages of `country`.
+    pub fn country(mut self, country: Country) -> Self {
+        self.inner.languages = country.default_languages().to_vec();
+        self
+    }
+
+    /// Use `str::parse` or `Language::try_from` to get the languages from their codes.
+    pub fn languages<I: IntoIterator<Item = Language>>(mut self, languages: I) -> Self {
+        self.inner.languages = languages.into_iter().collect();
+        self
+    }
+
+    /// Same as `languages`, from the language codes.
+    pub fn language_codes(mut self, codes: &[&str]) -> Result<Self, UnknownLanguage> {
+        self.inner.languages = Language::parse_all(codes)?;
+        Ok(self)
+    }
+
+    pub fn build(self) -> DuplicateOptions {
+        self.inner
+    }
 }
 
 impl ToC for DuplicateOptions {
@@ -331,7 +892,9 @@ impl ToRust for sys::libpostal_duplicate_options_t {
 
         let mut languages = Vec::with_capacity(self.num_languages);
         for i in 0..self.num_languages {
-            languages.push(unsafe { (*self.languages.offset(i as _)).to_rust() });
+            languages.push(Language::new(&unsafe {
+                (*self.languages.add(i)).to_rust()
+            }));
         }
         unsafe {
             sys::libpostal_expansion_array_destroy(self.languages, self.num_languages);
@@ -341,12 +904,71 @@ impl ToRust for sys::libpostal_duplicate_options_t {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
 pub struct FuzzyDuplicateOptions {
-    pub languages: Vec<String>,
+    pub languages: Vec<Language>,
     pub needs_review_threshold: f64,
     pub likely_dupe_threshold: f64,
 }
 
+impl Default for FuzzyDuplicateOptions {
+    /// Mirrors `libpostal_get_default_fuzzy_duplicate_options`.
+    fn default() -> FuzzyDuplicateOptions {
+        FuzzyDuplicateOptions {
+            languages: Vec::new(),
+            needs_review_threshold: 0.7,
+            likely_dupe_threshold: 0.9,
+        }
+    }
+}
+
+impl FuzzyDuplicateOptions {
+    /// Returns the default options using the default languages of `country`.
+    pub fn for_country(country: Country) -> FuzzyDuplicateOptions {
+        FuzzyDuplicateOptions::builder().country(country).build()
+    }
+
+    pub fn builder() -> FuzzyDuplicateOptionsBuilder {
+        FuzzyDuplicateOptionsBuilder {
+            inner: FuzzyDuplicateOptions::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FuzzyDuplicateOptionsBuilder {
+    inner: FuzzyDuplicateOptions,
+}
+
+impl FuzzyDuplicateOptionsBuilder {
+    /// Uses the default languages of `country`.
+    pub fn country(mut self, country: Country) -> Self {
+        self.inner.languages = country.default_languages().to_vec();
+        self
+    }
+
+    /// Use `str::parse` or `Language::try_from` to get the languages from their codes.
+    pub fn languages<I: IntoIterator<Item = Language>>(mut self, languages: I) -> Self {
+        self.inner.languages = languages.into_iter().collect();
+        self
+    }
+
+    /// Same as `languages`, from the language codes.
+    pub fn language_codes(mut self, codes: &[&str]) -> Result<Self, UnknownLanguage> {
+        self.inner.languages = Language::parse_all(codes)?;
+        Ok(self)
+    }
+
+    builder_setters! {
+        needs_review_threshold: f64,
+        likely_dupe_threshold: f64,
+    }
+
+    pub fn build(self) -> FuzzyDuplicateOptions {
+        self.inner
+    }
+}
+
 impl ToC for FuzzyDuplicateOptions {
     // Funnily enough, to not kill performances, we return a tuple containing:
     //
@@ -394,7 +1016,9 @@ impl ToRust for sys::libpostal_fuzzy_duplicate_options_t {
 
         let mut languages = Vec::with_capacity(self.num_languages);
         for i in 0..self.num_languages {
-            languages.push(unsafe { (*self.languages.offset(i as _)).to_rust() });
+            languages.push(Language::new(&unsafe {
+                (*self.languages.add(i)).to_rust()
+            }));
         }
         unsafe {
             sys::libpostal_expansion_array_destroy(
@@ -416,6 +1040,16 @@ pub struct FuzzyDuplicateStatus {
     similarity: f64,
 }
 
+impl FuzzyDuplicateStatus {
+    pub fn status(&self) -> DuplicateStatus {
+        self.status
+    }
+
+    pub fn similarity(&self) -> f64 {
+        self.similarity
+    }
+}
+
 impl ToC for FuzzyDuplicateStatus {
     type Out = sys::libpostal_fuzzy_duplicate_status_t;
 
@@ -439,3 +1073,117 @@ impl ToRust for sys::libpostal_fuzzy_duplicate_status_t {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geohash_precision_is_only_checked_with_latlon() {
+        let options = |with_latlon, geohash_precision| NearDupeHashOptions {
+            with_latlon,
+            geohash_precision,
+            ..NearDupeHashOptions::default()
+        };
+
+        assert_eq!(options(true, 1).validate(), Ok(()));
+        assert_eq!(options(true, MAX_GEOHASH_PRECISION).validate(), Ok(()));
+        assert_eq!(
+            options(true, 0).validate(),
+            Err(NearDupeHashError::InvalidGeohashPrecision(0))
+        );
+        assert_eq!(
+            options(true, MAX_GEOHASH_PRECISION + 1).validate(),
+            Err(NearDupeHashError::InvalidGeohashPrecision(
+                MAX_GEOHASH_PRECISION + 1
+            ))
+        );
+        assert_eq!(options(false, 0).validate(), Ok(()));
+    }
+
+    #[test]
+    fn latlon_bounds() {
+        for &(latitude, longitude) in &[(0., 0.), (90., 180.), (-90., -180.), (48.85, 2.35)] {
+            assert_eq!(validate_latlon(latitude, longitude), Ok(()));
+        }
+        assert_eq!(
+            validate_latlon(90.5, 0.),
+            Err(NearDupeHashError::InvalidLatitude(90.5))
+        );
+        assert_eq!(
+            validate_latlon(-91., 0.),
+            Err(NearDupeHashError::InvalidLatitude(-91.))
+        );
+        assert_eq!(
+            validate_latlon(0., 180.5),
+            Err(NearDupeHashError::InvalidLongitude(180.5))
+        );
+        assert_eq!(
+            validate_latlon(0., -181.),
+            Err(NearDupeHashError::InvalidLongitude(-181.))
+        );
+        assert!(validate_latlon(f64::NAN, 0.).is_err());
+        assert!(validate_latlon(0., f64::INFINITY).is_err());
+        // The latitude is checked first.
+        assert_eq!(
+            validate_latlon(100., 200.),
+            Err(NearDupeHashError::InvalidLatitude(100.))
+        );
+    }
+
+    #[test]
+    fn with_latlon_needs_coordinates() {
+        let options = NearDupeHashOptions {
+            with_latlon: true,
+            ..NearDupeHashOptions::default()
+        };
+
+        let c_options = options.to_c_at(Some((48.85, 2.35)));
+        assert!(c_options.with_latlon.to_rust());
+        assert_eq!((c_options.latitude, c_options.longitude), (48.85, 2.35));
+        assert!(!options.to_c().with_latlon.to_rust());
+        let without_flag = NearDupeHashOptions::default().to_c_at(Some((48.85, 2.35)));
+        assert!(!without_flag.with_latlon.to_rust());
+    }
+
+    #[test]
+    fn language_codes_are_validated() {
+        let options = DuplicateOptions::builder()
+            .language_codes(&["en", "fr"])
+            .unwrap()
+            .build();
+        assert_eq!(options.languages, vec![Language::English, Language::French]);
+        assert!(DuplicateOptions::builder()
+            .language_codes(&["en", "fra"])
+            .is_err());
+    }
+
+    #[test]
+    fn address_components_round_trip() {
+        for bits in 0..=u16::MAX {
+            let components = AddressComponents::from_bits(bits);
+            let s = components.to_string();
+            assert_eq!(s.parse(), Ok(components), "{}", s);
+        }
+    }
+
+    #[test]
+    fn address_components_display() {
+        let components =
+            AddressComponents::from(AddressComponent::Street) | AddressComponent::Toponym;
+        assert_eq!(components.to_string(), "street|toponym");
+        assert_eq!(AddressComponents::empty().to_string(), "none");
+        assert_eq!(AddressComponents::all().to_string(), "all");
+        assert_eq!(
+            " Street | TOPONYM ".parse::<AddressComponents>(),
+            Ok(components)
+        );
+        assert_eq!("none".parse(), Ok(AddressComponents::empty()));
+        assert_eq!("all".parse(), Ok(AddressComponents::all()));
+        assert_eq!(
+            "street|road".parse::<AddressComponents>(),
+            Err(UnknownAddressComponent("road".to_owned()))
+        );
+        assert!("0xzz".parse::<AddressComponents>().is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use sys;
+
+use std::ffi::{CStr, CString};
+
+use libc::c_char;
+
+use Address;
+
+/// Owns the result of `libpostal_parse_address` and gives borrowed access to its components
+/// without copying them.
+///
+/// The underlying memory is released when this value is dropped.
+pub struct ParseResponse {
+    inner: *mut sys::libpostal_address_parser_response_t,
+}
+
+impl ParseResponse {
+    /// `inner` must be a non-null pointer returned by `libpostal_parse_address`.
+    pub(crate) unsafe fn new(
+        inner: *mut sys::libpostal_address_parser_response_t,
+    ) -> ParseResponse {
+        ParseResponse { inner }
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { (*self.inner).num_components }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the `(label, value)` pair at `pos`.
+    ///
+    /// libpostal always returns UTF-8 strings; if it ever didn't, the invalid string is returned
+    /// as an empty one.
+    pub fn get(&self, pos: usize) -> Option<(&str, &str)> {
+        if pos >= self.len() {
+            return None;
+        }
+        unsafe {
+            let data = &*self.inner;
+            Some((
+                borrow_str(*data.labels.add(pos)),
+                borrow_str(*data.components.add(pos)),
+            ))
+        }
+    }
+
+    /// Returns the value of the first component with the given label.
+    pub fn get_label(&self, label: &str) -> Option<&str> {
+        self.iter().find(|&(l, _)| l == label).map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> ParseResponseIter<'_> {
+        ParseResponseIter {
+            response: self,
+            pos: 0,
+        }
+    }
+
+    /// Copies the components into owned `Address` values.
+    pub fn to_addresses(&self) -> Vec<Address> {
+        self.iter()
+            .map(|(label, value)| Address {
+                label: CString::new(label).expect("unexpected '\0' in label"),
+                value: CString::new(value).expect("unexpected '\0' in value"),
+            })
+            .collect()
+    }
+}
+
+impl Drop for ParseResponse {
+    fn drop(&mut self) {
+        unsafe { sys::libpostal_address_parser_response_destroy(self.inner) }
+    }
+}
+
+impl<'a> IntoIterator for &'a ParseResponse {
+    type Item = (&'a str, &'a str);
+    type IntoIter = ParseResponseIter<'a>;
+
+    fn into_iter(self) -> ParseResponseIter<'a> {
+        self.iter()
+    }
+}
+
+pub struct ParseResponseIter<'a> {
+    response: &'a ParseResponse,
+    pos: usize,
+}
+
+impl<'a> Iterator for ParseResponseIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ret = self.response.get(self.pos)?;
+        self.pos += 1;
+        Some(ret)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.response.len().saturating_sub(self.pos);
+        (len, Some(len))
+    }
+}
+
+impl<'a> ExactSizeIterator for ParseResponseIter<'a> {}
+
+unsafe fn borrow_str<'a>(ptr: *const c_char) -> &'a str {
+    if ptr.is_null() {
+        return "";
+    }
+    CStr::from_ptr(ptr).to_str().unwrap_or("")
+}
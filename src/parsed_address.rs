@@ -0,0 +1,194 @@
+use std::ffi::CString;
+
+use Address;
+use AddressComponent;
+
+/// Labels returned by the libpostal address parser, in the order they usually appear.
+pub static LABELS: &[&str] = &[
+    "house",
+    "category",
+    "near",
+    "house_number",
+    "road",
+    "unit",
+    "level",
+    "staircase",
+    "entrance",
+    "po_box",
+    "postcode",
+    "suburb",
+    "city_district",
+    "city",
+    "island",
+    "state_district",
+    "state",
+    "country_region",
+    "country",
+    "world_region",
+];
+
+/// Returns the `AddressComponent` matching a parser label, to restrict expansions to it.
+pub fn component_for_label(label: &str) -> Option<AddressComponent> {
+    Some(match label {
+        "house" => AddressComponent::Name,
+        "category" => AddressComponent::Category,
+        "near" => AddressComponent::Near,
+        "house_number" => AddressComponent::HouseNumber,
+        "road" => AddressComponent::Street,
+        "unit" => AddressComponent::Unit,
+        "level" => AddressComponent::Level,
+        "staircase" => AddressComponent::Staircase,
+        "entrance" => AddressComponent::Entrance,
+        "po_box" => AddressComponent::POBox,
+        "postcode" => AddressComponent::PostalCode,
+        "suburb" | "city_district" | "city" | "island" | "state_district" | "state"
+        | "country_region" | "country" | "world_region" => AddressComponent::Toponym,
+        _ => return None,
+    })
+}
+
+/// Typed view over the components returned by `AddressParser::parse_address`.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ParsedAddress {
+    pub house: Option<String>,
+    pub category: Option<String>,
+    pub near: Option<String>,
+    pub house_number: Option<String>,
+    pub road: Option<String>,
+    pub unit: Option<String>,
+    pub level: Option<String>,
+    pub staircase: Option<String>,
+    pub entrance: Option<String>,
+    pub po_box: Option<String>,
+    pub postcode: Option<String>,
+    pub suburb: Option<String>,
+    pub city_district: Option<String>,
+    pub city: Option<String>,
+    pub island: Option<String>,
+    pub state_district: Option<String>,
+    pub state: Option<String>,
+    pub country_region: Option<String>,
+    pub country: Option<String>,
+    pub world_region: Option<String>,
+}
+
+impl ParsedAddress {
+    pub fn new() -> ParsedAddress {
+        ParsedAddress::default()
+    }
+
+    /// Builds a `ParsedAddress` from the parser output. If a label appears more than once, the
+    /// values are joined with a space. Unknown labels are ignored.
+    pub fn from_addresses(addresses: &[Address]) -> ParsedAddress {
+        let mut ret = ParsedAddress::default();
+
+        for address in addresses {
+            let label = address.label.to_string_lossy();
+            let value = address.value.to_string_lossy();
+            if let Some(field) = ret.field_mut(&label) {
+                match *field {
+                    Some(ref mut v) => {
+                        v.push(' ');
+                        v.push_str(&value);
+                    }
+                    None => *field = Some(value.into_owned()),
+                }
+            }
+        }
+        ret
+    }
+
+    /// Returns the value of the given label.
+    pub fn get(&self, label: &str) -> Option<&str> {
+        self.field(label).and_then(|v| v.as_deref())
+    }
+
+    /// Sets the value of the given label. Returns `false` if the label is unknown.
+    pub fn set<S: Into<String>>(&mut self, label: &str, value: Option<S>) -> bool {
+        match self.field_mut(label) {
+            Some(field) => {
+                *field = value.map(|v| v.into());
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Iterates over the `(label, value)` of the set components, in `LABELS` order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        LABELS
+            .iter()
+            .filter_map(move |label| self.get(label).map(|v| (*label, v)))
+    }
+
+    pub fn to_addresses(&self) -> Vec<Address> {
+        self.iter()
+            .map(|(label, value)| Address {
+                label: CString::new(label).expect("unexpected '\0' in label"),
+                value: CString::new(value).expect("unexpected '\0' in value"),
+            })
+            .collect()
+    }
+
+    fn field(&self, label: &str) -> Option<&Option<String>> {
+        Some(match label {
+            "house" => &self.house,
+            "category" => &self.category,
+            "near" => &self.near,
+            "house_number" => &self.house_number,
+            "road" => &self.road,
+            "unit" => &self.unit,
+            "level" => &self.level,
+            "staircase" => &self.staircase,
+            "entrance" => &self.entrance,
+            "po_box" => &self.po_box,
+            "postcode" => &self.postcode,
+            "suburb" => &self.suburb,
+            "city_district" => &self.city_district,
+            "city" => &self.city,
+            "island" => &self.island,
+            "state_district" => &self.state_district,
+            "state" => &self.state,
+            "country_region" => &self.country_region,
+            "country" => &self.country,
+            "world_region" => &self.world_region,
+            _ => return None,
+        })
+    }
+
+    fn field_mut(&mut self, label: &str) -> Option<&mut Option<String>> {
+        Some(match label {
+            "house" => &mut self.house,
+            "category" => &mut self.category,
+            "near" => &mut self.near,
+            "house_number" => &mut self.house_number,
+            "road" => &mut self.road,
+            "unit" => &mut self.unit,
+            "level" => &mut self.level,
+            "staircase" => &mut self.staircase,
+            "entrance" => &mut self.entrance,
+            "po_box" => &mut self.po_box,
+            "postcode" => &mut self.postcode,
+            "suburb" => &mut self.suburb,
+            "city_district" => &mut self.city_district,
+            "city" => &mut self.city,
+            "island" => &mut self.island,
+            "state_district" => &mut self.state_district,
+            "state" => &mut self.state,
+            "country_region" => &mut self.country_region,
+            "country" => &mut self.country,
+            "world_region" => &mut self.world_region,
+            _ => return None,
+        })
+    }
+}
+
+impl<'a> From<&'a [Address]> for ParsedAddress {
+    fn from(addresses: &'a [Address]) -> ParsedAddress {
+        ParsedAddress::from_addresses(addresses)
+    }
+}
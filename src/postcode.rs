@@ -0,0 +1,360 @@
+//! Postal code validation and normalization.
+//!
+//! Formats are described with patterns where `9` is a digit, `A` is a letter, `?` is a letter or
+//! a digit and any other character is a separator which only appears in the canonical form.
+
+use Address;
+use Country;
+
+// Countries which don't use postal codes at all.
+static NO_POSTCODES: &[&str] = &[
+    "AE", "AG", "AO", "AW", "BF", "BI", "BJ", "BO", "BS", "BW", "BZ", "CD", "CF", "CG", "CI", "CK",
+    "CM", "DJ", "DM", "ER", "FJ", "GA", "GD", "GM", "GQ", "GY", "HK", "KI", "KM", "KN", "KP", "LC",
+    "ML", "MO", "MR", "MW", "NR", "NU", "QA", "RW", "SB", "SC", "SL", "SR", "ST", "SY", "TD", "TF",
+    "TG", "TK", "TL", "TO", "TV", "UG", "VU", "YE", "ZW",
+];
+
+static UK_PATTERNS: &[&str] = &[
+    "A9 9AA", "A99 9AA", "A9A 9AA", "AA9 9AA", "AA99 9AA", "AA9A 9AA",
+];
+
+static PATTERNS: &[(&str, &[&str])] = &[
+    ("AM", &["9999"]),
+    ("AR", &["9999", "A9999AAA"]),
+    ("AT", &["9999"]),
+    ("AU", &["9999"]),
+    ("BE", &["9999"]),
+    ("BG", &["9999"]),
+    ("BR", &["99999-999"]),
+    ("BY", &["999999"]),
+    ("CA", &["A9A 9A9"]),
+    ("CH", &["9999"]),
+    ("CN", &["999999"]),
+    ("CO", &["999999"]),
+    ("CY", &["9999"]),
+    ("CZ", &["999 99"]),
+    ("DE", &["99999"]),
+    ("DK", &["9999"]),
+    ("DZ", &["99999"]),
+    ("EC", &["999999"]),
+    ("EE", &["99999"]),
+    ("ES", &["99999"]),
+    ("FI", &["99999"]),
+    ("FO", &["999"]),
+    ("FR", &["99999"]),
+    ("GB", UK_PATTERNS),
+    ("GE", &["9999"]),
+    ("GG", UK_PATTERNS),
+    ("GR", &["999 99"]),
+    ("HR", &["99999"]),
+    ("HU", &["9999"]),
+    ("ID", &["99999"]),
+    ("IE", &["A99 ????", "A9A ????"]),
+    ("IL", &["9999999"]),
+    ("IM", UK_PATTERNS),
+    ("IN", &["999999"]),
+    ("IS", &["999"]),
+    ("IT", &["99999"]),
+    ("JE", UK_PATTERNS),
+    ("JP", &["999-9999"]),
+    ("KR", &["99999"]),
+    ("LT", &["99999"]),
+    ("LU", &["9999"]),
+    ("LV", &["9999"]),
+    ("MA", &["99999"]),
+    ("MX", &["99999"]),
+    ("MY", &["99999"]),
+    ("NG", &["999999"]),
+    ("NL", &["9999 AA"]),
+    ("NO", &["9999"]),
+    ("NZ", &["9999"]),
+    ("PH", &["9999"]),
+    ("PL", &["99-999"]),
+    ("PT", &["9999-999"]),
+    ("RO", &["999999"]),
+    ("RU", &["999999"]),
+    ("SA", &["99999", "99999-9999"]),
+    ("SE", &["999 99"]),
+    ("SG", &["999999"]),
+    ("SI", &["9999"]),
+    ("SK", &["999 99"]),
+    ("TH", &["99999"]),
+    ("TN", &["9999"]),
+    ("TR", &["99999"]),
+    ("TW", &["999", "99999", "999999"]),
+    ("UA", &["99999"]),
+    ("US", &["99999", "99999-9999"]),
+    ("VN", &["999999"]),
+    ("ZA", &["9999"]),
+];
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum PostcodeValidation {
+    /// The postal code matches one of the country formats. Contains its canonical form.
+    Valid(String),
+    /// The postal code doesn't match any of the country formats, or the country doesn't use
+    /// postal codes.
+    Invalid,
+    /// The formats of this country aren't known.
+    Unsupported,
+}
+
+/// Returns `Some(false)` if `country` doesn't use postal codes, `Some(true)` if it does and
+/// `None` if it's unknown.
+pub fn uses_postcodes(country: Country) -> Option<bool> {
+    if NO_POSTCODES.contains(&country.code()) {
+        Some(false)
+    } else if patterns(country).is_some() {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Checks `code` against the postal code formats of `country`.
+pub fn validate(code: &str, country: Country) -> PostcodeValidation {
+    if uses_postcodes(country) == Some(false) {
+        return PostcodeValidation::Invalid;
+    }
+    let patterns = match patterns(country) {
+        Some(p) => p,
+        None => return PostcodeValidation::Unsupported,
+    };
+    let compact = compact(code);
+    patterns
+        .iter()
+        .filter_map(|pattern| format(&compact, pattern))
+        .next()
+        .map(PostcodeValidation::Valid)
+        .unwrap_or(PostcodeValidation::Invalid)
+}
+
+/// Returns the canonical form of `code` (for example `"sw1a1aa"` becomes `"SW1A 1AA"` in `GB`)
+/// or `None` if it isn't valid.
+pub fn normalize(code: &str, country: Country) -> Option<String> {
+    match validate(code, country) {
+        PostcodeValidation::Valid(s) => Some(s),
+        _ => None,
+    }
+}
+
+/// Country-agnostic check: a postal code is made of 2 to 10 letters and digits (spaces and
+/// hyphens aside) and contains at least one digit.
+pub fn is_plausible(code: &str) -> bool {
+    let compact = compact(code);
+    (2..=10).contains(&compact.len())
+        && compact.chars().all(|c| c.is_ascii_alphanumeric())
+        && compact.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Returns the components labeled as `postcode` by the parser which cannot be postal codes.
+///
+/// If `country` is `None` or its formats aren't known, `is_plausible` is used.
+pub fn suspicious_postcodes(addresses: &[Address], country: Option<Country>) -> Vec<&Address> {
+    addresses
+        .iter()
+        .filter(|a| a.label.as_bytes() == b"postcode")
+        .filter(|a| {
+            let value = a.value.to_string_lossy();
+            match country.map(|c| validate(&value, c)) {
+                Some(PostcodeValidation::Valid(_)) => false,
+                Some(PostcodeValidation::Invalid) => true,
+                Some(PostcodeValidation::Unsupported) | None => !is_plausible(&value),
+            }
+        })
+        .collect()
+}
+
+fn patterns(country: Country) -> Option<&'static [&'static str]> {
+    PATTERNS
+        .binary_search_by(|&(c, _)| c.cmp(country.code()))
+        .ok()
+        .map(|pos| PATTERNS[pos].1)
+}
+
+fn compact(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '.')
+        .flat_map(|c| c.to_uppercase())
+        .collect()
+}
+
+// Returns the canonical form of `compact` if it matches `pattern`.
+fn format(compact: &str, pattern: &str) -> Option<String> {
+    let mut chars = compact.chars();
+    let mut ret = String::with_capacity(pattern.len());
+
+    for p in pattern.chars() {
+        let matches: fn(&char) -> bool = match p {
+            '9' => char::is_ascii_digit,
+            'A' => char::is_ascii_uppercase,
+            '?' => |c: &char| c.is_ascii_digit() || c.is_ascii_uppercase(),
+            sep => {
+                ret.push(sep);
+                continue;
+            }
+        };
+        match chars.next() {
+            Some(c) if matches(&c) => ret.push(c),
+            _ => return None,
+        }
+    }
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    fn country(code: &str) -> Country {
+        code.parse().unwrap()
+    }
+
+    fn valid(code: &str, country_code: &str) -> Option<String> {
+        normalize(code, country(country_code))
+    }
+
+    #[test]
+    fn tables_are_sorted() {
+        // `patterns` relies on it for its binary search.
+        for pair in PATTERNS.windows(2) {
+            assert!(pair[0].0 < pair[1].0, "{} >= {}", pair[0].0, pair[1].0);
+        }
+        for pair in NO_POSTCODES.windows(2) {
+            assert!(pair[0] < pair[1], "{} >= {}", pair[0], pair[1]);
+        }
+        for code in NO_POSTCODES {
+            assert!(code.parse::<Country>().is_ok(), "{}", code);
+            assert!(PATTERNS.iter().all(|&(c, _)| c != *code), "{}", code);
+        }
+        for &(code, _) in PATTERNS {
+            assert!(code.parse::<Country>().is_ok(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn digits() {
+        assert_eq!(valid("75011", "FR").as_deref(), Some("75011"));
+        assert_eq!(valid(" 7501 1 ", "FR").as_deref(), Some("75011"));
+        assert_eq!(valid("7501", "FR"), None);
+        assert_eq!(valid("750111", "FR"), None);
+        assert_eq!(valid("7501A", "FR"), None);
+        assert_eq!(valid("3011", "CH").as_deref(), Some("3011"));
+    }
+
+    #[test]
+    fn separators_are_canonicalized() {
+        assert_eq!(valid("1000001", "JP").as_deref(), Some("100-0001"));
+        assert_eq!(valid("100 0001", "JP").as_deref(), Some("100-0001"));
+        assert_eq!(valid("00950", "PL").as_deref(), Some("00-950"));
+        assert_eq!(valid("1000-001", "PT").as_deref(), Some("1000-001"));
+        assert_eq!(valid("01310.100", "BR").as_deref(), Some("01310-100"));
+        assert_eq!(valid("11455", "SE").as_deref(), Some("114 55"));
+        assert_eq!(valid("110-00", "CZ").as_deref(), Some("110 00"));
+    }
+
+    #[test]
+    fn alternatives() {
+        assert_eq!(valid("94103", "US").as_deref(), Some("94103"));
+        assert_eq!(valid("94103-1234", "US").as_deref(), Some("94103-1234"));
+        assert_eq!(valid("941031234", "US").as_deref(), Some("94103-1234"));
+        assert_eq!(valid("9410312", "US"), None);
+        assert_eq!(valid("1425", "AR").as_deref(), Some("1425"));
+        assert_eq!(valid("c1425dkd", "AR").as_deref(), Some("C1425DKD"));
+        assert_eq!(valid("100", "TW").as_deref(), Some("100"));
+        assert_eq!(valid("10001", "TW").as_deref(), Some("10001"));
+        assert_eq!(valid("1000", "TW"), None);
+    }
+
+    #[test]
+    fn letters_and_digits() {
+        assert_eq!(valid("h0h0h0", "CA").as_deref(), Some("H0H 0H0"));
+        assert_eq!(valid("H0H0H", "CA"), None);
+        assert_eq!(valid("1012ab", "NL").as_deref(), Some("1012 AB"));
+        assert_eq!(valid("1012 a1", "NL"), None);
+        assert_eq!(valid("d02x285", "IE").as_deref(), Some("D02 X285"));
+        assert_eq!(valid("A65 F4E2", "IE").as_deref(), Some("A65 F4E2"));
+        assert_eq!(valid("D02X28", "IE"), None);
+    }
+
+    #[test]
+    fn uk() {
+        for (code, expected) in &[
+            ("m11ae", "M1 1AE"),
+            ("b338th", "B33 8TH"),
+            ("w1a0ax", "W1A 0AX"),
+            ("cr26xh", "CR2 6XH"),
+            ("dn551pt", "DN55 1PT"),
+            ("sw1a 1aa", "SW1A 1AA"),
+        ] {
+            assert_eq!(valid(code, "GB").as_deref(), Some(*expected));
+        }
+        assert_eq!(valid("je24wd", "JE").as_deref(), Some("JE2 4WD"));
+        assert_eq!(valid("SW1A1A", "GB"), None);
+        assert_eq!(valid("1W1A 1AA", "GB"), None);
+    }
+
+    #[test]
+    fn countries_without_postcodes() {
+        for code in &["AE", "HK", "QA", "ZW"] {
+            assert_eq!(uses_postcodes(country(code)), Some(false));
+            assert_eq!(
+                validate("12345", country(code)),
+                PostcodeValidation::Invalid
+            );
+        }
+        assert_eq!(uses_postcodes(country("FR")), Some(true));
+    }
+
+    #[test]
+    fn unsupported_countries() {
+        // Andorra uses postal codes but its format isn't listed.
+        assert_eq!(uses_postcodes(country("AD")), None);
+        assert_eq!(
+            validate("AD500", country("AD")),
+            PostcodeValidation::Unsupported
+        );
+        assert_eq!(normalize("AD500", country("AD")), None);
+    }
+
+    #[test]
+    fn plausibility() {
+        assert!(is_plausible("75011"));
+        assert!(is_plausible("sw1a 1aa"));
+        assert!(is_plausible("94103-1234"));
+        assert!(is_plausible("AD500"));
+        assert!(!is_plausible("1"));
+        assert!(!is_plausible("12345678901"));
+        assert!(!is_plausible("london"));
+        assert!(!is_plausible("75011!"));
+        assert!(!is_plausible("７５０１１"));
+    }
+
+    #[test]
+    fn suspicious() {
+        let address = |label: &str, value: &str| Address {
+            label: CString::new(label).unwrap(),
+            value: CString::new(value).unwrap(),
+        };
+        let addresses = vec![
+            address("road", "rue de rivoli"),
+            address("postcode", "75001"),
+            address("postcode", "paris"),
+            address("postcode", "7500"),
+        ];
+        let values = |s: Vec<&Address>| {
+            s.iter()
+                .map(|a| a.value.to_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            values(suspicious_postcodes(&addresses, Some(country("FR")))),
+            ["paris", "7500"]
+        );
+        assert_eq!(values(suspicious_postcodes(&addresses, None)), ["paris"]);
+    }
+}
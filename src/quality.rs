@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use postcode::{self, PostcodeValidation};
+
+use Address;
+use Country;
+
+/// A problem found in a parse by `ParseQuality::assess`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// A component expected for the country is missing.
+    MissingComponent(&'static str),
+    /// The label was given to more than one component.
+    DuplicateLabel(String),
+    /// Words of the input which didn't end up in any component.
+    UnlabeledResidue(Vec<String>),
+    /// The house number doesn't contain any digit.
+    NonNumericHouseNumber(String),
+    /// The postcode isn't valid for the country (or can't be a postcode at all).
+    InvalidPostcode(String),
+}
+
+impl ParseWarning {
+    /// Returns a stable machine-readable identifier of the warning kind.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            ParseWarning::MissingComponent(_) => "missing_component",
+            ParseWarning::DuplicateLabel(_) => "duplicate_label",
+            ParseWarning::UnlabeledResidue(_) => "unlabeled_residue",
+            ParseWarning::NonNumericHouseNumber(_) => "non_numeric_house_number",
+            ParseWarning::InvalidPostcode(_) => "invalid_postcode",
+        }
+    }
+
+    /// How much this warning lowers the score.
+    pub fn penalty(&self) -> f64 {
+        match *self {
+            ParseWarning::MissingComponent(_) => 0.2,
+            ParseWarning::DuplicateLabel(_) => 0.15,
+            ParseWarning::UnlabeledResidue(ref words) => (0.1 * words.len() as f64).min(0.3),
+            ParseWarning::NonNumericHouseNumber(_) => 0.1,
+            ParseWarning::InvalidPostcode(_) => 0.2,
+        }
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseWarning::MissingComponent(label) => write!(f, "missing {}", label),
+            ParseWarning::DuplicateLabel(ref label) => write!(f, "duplicated label {}", label),
+            ParseWarning::UnlabeledResidue(ref words) => {
+                write!(f, "unlabeled words: {}", words.join(" "))
+            }
+            ParseWarning::NonNumericHouseNumber(ref v) => {
+                write!(f, "house number {:?} isn't numeric", v)
+            }
+            ParseWarning::InvalidPostcode(ref v) => write!(f, "invalid postcode {:?}", v),
+        }
+    }
+}
+
+/// Quality report of a parse, to route doubtful ones to manual review.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseQuality {
+    /// From `0.` (unusable) to `1.` (no warning).
+    pub score: f64,
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl ParseQuality {
+    /// Checks the output of `AddressParser::parse_address` for `input`.
+    ///
+    /// If `country` is `None`, only the road and city are expected and postcodes are checked
+    /// with `postcode::is_plausible`.
+    pub fn assess(input: &str, addresses: &[Address], country: Option<Country>) -> ParseQuality {
+        let mut warnings = Vec::new();
+        let mut labels: HashMap<String, Vec<String>> = HashMap::new();
+
+        for address in addresses {
+            labels
+                .entry(address.label.to_string_lossy().into_owned())
+                .or_default()
+                .push(address.value.to_string_lossy().into_owned());
+        }
+
+        let mut expected = vec!["road", "city"];
+        if labels.contains_key("road") {
+            expected.insert(0, "house_number");
+        }
+        if country.and_then(postcode::uses_postcodes) == Some(true) {
+            expected.push("postcode");
+        }
+        for label in expected {
+            if !labels.contains_key(label) {
+                warnings.push(ParseWarning::MissingComponent(label));
+            }
+        }
+
+        let mut duplicates = labels
+            .iter()
+            .filter(|&(_, values)| values.len() > 1)
+            .map(|(label, _)| label.clone())
+            .collect::<Vec<_>>();
+        duplicates.sort();
+        warnings.extend(duplicates.into_iter().map(ParseWarning::DuplicateLabel));
+
+        let residue = residue(input, addresses);
+        if !residue.is_empty() {
+            warnings.push(ParseWarning::UnlabeledResidue(residue));
+        }
+
+        for value in labels.get("house_number").into_iter().flatten() {
+            if !value.chars().any(|c| c.is_numeric()) {
+                warnings.push(ParseWarning::NonNumericHouseNumber(value.clone()));
+            }
+        }
+        for value in labels.get("postcode").into_iter().flatten() {
+            let valid = match country.map(|c| postcode::validate(value, c)) {
+                Some(PostcodeValidation::Valid(_)) => true,
+                Some(PostcodeValidation::Invalid) => false,
+                Some(PostcodeValidation::Unsupported) | None => postcode::is_plausible(value),
+            };
+            if !valid {
+                warnings.push(ParseWarning::InvalidPostcode(value.clone()));
+            }
+        }
+
+        let score = warnings
+            .iter()
+            .fold(1., |score, w| score - w.penalty())
+            .max(0.);
+        ParseQuality { score, warnings }
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+}
+
+fn words(s: &str) -> impl Iterator<Item = String> + '_ {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+}
+
+// Words of the input which don't appear in any component. Words are matched whole, but
+// components are also compared without their separators since libpostal may join or split words
+// (in postcodes for example), in which case a run of input words can match a single one.
+fn residue(input: &str, addresses: &[Address]) -> Vec<String> {
+    let values = addresses
+        .iter()
+        .map(|a| a.value.to_string_lossy())
+        .collect::<Vec<_>>();
+    let mut labeled = values.iter().flat_map(|v| words(v)).collect::<HashSet<_>>();
+    labeled.extend(values.iter().map(|v| words(v).collect::<String>()));
+    let longest = labeled.iter().map(String::len).max().unwrap_or(0);
+
+    let input = words(input).collect::<Vec<_>>();
+    let mut covered = vec![false; input.len()];
+    for start in 0..input.len() {
+        let mut joined = String::new();
+        for end in start..input.len() {
+            joined.push_str(&input[end]);
+            if joined.len() > longest {
+                break;
+            }
+            if labeled.contains(&joined) {
+                for c in &mut covered[start..=end] {
+                    *c = true;
+                }
+            }
+        }
+    }
+
+    input
+        .into_iter()
+        .zip(covered)
+        .filter(|&(_, covered)| !covered)
+        .map(|(w, _)| w)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    fn parsed(components: &[(&str, &str)]) -> Vec<Address> {
+        components
+            .iter()
+            .map(|&(label, value)| Address {
+                label: CString::new(label).unwrap(),
+                value: CString::new(value).unwrap(),
+            })
+            .collect()
+    }
+
+    fn country(code: &str) -> Option<Country> {
+        Some(code.parse().unwrap())
+    }
+
+    #[test]
+    fn complete_parse_has_no_warning() {
+        let input = "10 Downing Street, London SW1A 2AA";
+        let addresses = parsed(&[
+            ("house_number", "10"),
+            ("road", "downing street"),
+            ("city", "london"),
+            ("postcode", "sw1a 2aa"),
+        ]);
+
+        let quality = ParseQuality::assess(input, &addresses, country("GB"));
+        assert_eq!(quality.warnings, vec![]);
+        assert_eq!(quality.score, 1.);
+        assert!(!quality.has_warnings());
+    }
+
+    #[test]
+    fn missing_components() {
+        let addresses = parsed(&[("road", "rue de rivoli"), ("postcode", "75001")]);
+
+        let quality = ParseQuality::assess("rue de rivoli 75001", &addresses, country("FR"));
+        assert_eq!(
+            quality.warnings,
+            vec![
+                ParseWarning::MissingComponent("house_number"),
+                ParseWarning::MissingComponent("city"),
+            ]
+        );
+        assert!((quality.score - 0.6).abs() < 1e-9);
+
+        // Without a road, no house number is expected, and without a country no postcode.
+        let addresses = parsed(&[("city", "paris")]);
+        let quality = ParseQuality::assess("paris", &addresses, None);
+        assert_eq!(
+            quality.warnings,
+            vec![ParseWarning::MissingComponent("road")]
+        );
+    }
+
+    #[test]
+    fn duplicate_labels() {
+        let input = "1 main street 2 main street springfield";
+        let addresses = parsed(&[
+            ("house_number", "1"),
+            ("road", "main street"),
+            ("house_number", "2"),
+            ("road", "main street"),
+            ("city", "springfield"),
+        ]);
+
+        let quality = ParseQuality::assess(input, &addresses, None);
+        assert_eq!(
+            quality.warnings,
+            vec![
+                ParseWarning::DuplicateLabel("house_number".to_owned()),
+                ParseWarning::DuplicateLabel("road".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unlabeled_residue() {
+        let input = "1 Main Street, Springfield, attn Bob";
+        let addresses = parsed(&[
+            ("house_number", "1"),
+            ("road", "main street"),
+            ("city", "springfield"),
+        ]);
+
+        let quality = ParseQuality::assess(input, &addresses, None);
+        assert_eq!(
+            quality.warnings,
+            vec![ParseWarning::UnlabeledResidue(vec![
+                "attn".to_owned(),
+                "bob".to_owned(),
+            ])]
+        );
+        assert!((quality.score - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn residue_matches_whole_words() {
+        // "main" and "spring" are parts of labeled words but weren't labeled themselves.
+        let input = "1 main street springfield spring";
+        let addresses = parsed(&[
+            ("house_number", "1"),
+            ("road", "maine street"),
+            ("city", "springfield"),
+        ]);
+
+        assert_eq!(residue(input, &addresses), vec!["main", "spring"]);
+    }
+
+    #[test]
+    fn residue_allows_joined_and_split_words() {
+        // libpostal split "sw1a2aa" and joined "75 001".
+        let addresses = parsed(&[("postcode", "sw1a 2aa"), ("postcode", "75001")]);
+
+        assert!(residue("SW1A2AA 75 001", &addresses).is_empty());
+        assert_eq!(residue("SW1A2AA 75 0", &addresses), vec!["75", "0"]);
+    }
+
+    #[test]
+    fn non_numeric_house_number() {
+        let addresses = parsed(&[
+            ("house_number", "one"),
+            ("road", "main street"),
+            ("city", "springfield"),
+        ]);
+
+        let quality = ParseQuality::assess("one main street springfield", &addresses, None);
+        assert_eq!(
+            quality.warnings,
+            vec![ParseWarning::NonNumericHouseNumber("one".to_owned())]
+        );
+        assert!((quality.score - 0.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn invalid_postcode() {
+        let input = "1 rue de rivoli paris 7500";
+        let addresses = parsed(&[
+            ("house_number", "1"),
+            ("road", "rue de rivoli"),
+            ("city", "paris"),
+            ("postcode", "7500"),
+        ]);
+
+        let quality = ParseQuality::assess(input, &addresses, country("FR"));
+        assert_eq!(
+            quality.warnings,
+            vec![ParseWarning::InvalidPostcode("7500".to_owned())]
+        );
+
+        // Without a country, any plausible postcode is accepted.
+        assert!(!ParseQuality::assess(input, &addresses, None).has_warnings());
+    }
+
+    #[test]
+    fn score_is_clamped() {
+        let input = "a b c d e";
+        let addresses = parsed(&[("postcode", "!"), ("postcode", "?"), ("house_number", "x")]);
+
+        let quality = ParseQuality::assess(input, &addresses, country("FR"));
+        assert!(quality.warnings.len() >= 5);
+        assert_eq!(quality.score, 0.);
+    }
+
+    #[test]
+    fn residue_penalty_is_capped() {
+        let words = vec!["a".to_owned(); 10];
+        assert_eq!(ParseWarning::UnlabeledResidue(words).penalty(), 0.3);
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeSet;
+
+use Core;
+use NormalizeOptions;
+
+/// Options for `Core::search_keys`.
+#[derive(Clone, Debug)]
+pub struct KeyOptions {
+    pub normalize: NormalizeOptions,
+    /// Include the outputs of `Core::expand_address`.
+    pub expansions: bool,
+    /// Include the outputs of `Core::expand_address_root`.
+    pub roots: bool,
+    /// If set, token n-grams (with `n` in this inclusive range) of each key are added as well.
+    pub ngrams: Option<(usize, usize)>,
+}
+
+impl Default for KeyOptions {
+    fn default() -> KeyOptions {
+        KeyOptions {
+            normalize: NormalizeOptions::default(),
+            expansions: true,
+            roots: true,
+            ngrams: None,
+        }
+    }
+}
+
+impl Core {
+    /// Returns the keys to index `input` with: the union of its expansions and root expansions,
+    /// and optionally their token n-grams.
+    ///
+    /// Keys are deduplicated and sorted so the output is stable across runs.
+    pub fn search_keys(&self, input: &str, options: &KeyOptions) -> BTreeSet<String> {
+        let mut keys = BTreeSet::new();
+
+        if options.expansions {
+            keys.extend(self.expand_address(input, options.normalize.clone()));
+        }
+        if options.roots {
+            keys.extend(self.expand_address_root(input, options.normalize.clone()));
+        }
+        if let Some((min, max)) = options.ngrams {
+            let mut ngrams = Vec::new();
+
+            for key in keys.iter() {
+                let words = self
+                    .tokenize(key, false)
+                    .into_iter()
+                    .filter(|t| !t.token_type.is_punctuation() && !t.token_type.is_whitespace())
+                    .map(|t| t.text(key))
+                    .collect::<Vec<_>>();
+                add_ngrams(&words, min, max, &mut ngrams);
+            }
+            keys.extend(ngrams);
+        }
+        keys.retain(|k| !k.is_empty());
+        keys
+    }
+}
+
+// Adds the n-grams of `words` with `n` in `min..=max` (`min` being at least 1) to `ngrams`.
+fn add_ngrams(words: &[&str], min: usize, max: usize, ngrams: &mut Vec<String>) {
+    for n in ::std::cmp::max(min, 1)..=max {
+        if n > words.len() {
+            break;
+        }
+        ngrams.extend(words.windows(n).map(|w| w.join(" ")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ngrams(words: &[&str], min: usize, max: usize) -> Vec<String> {
+        let mut ngrams = Vec::new();
+        add_ngrams(words, min, max, &mut ngrams);
+        ngrams
+    }
+
+    #[test]
+    fn ngrams_of_each_size() {
+        let words = ["30", "west", "26th", "street"];
+
+        assert_eq!(ngrams(&words, 1, 1), ["30", "west", "26th", "street"]);
+        assert_eq!(
+            ngrams(&words, 2, 3),
+            [
+                "30 west",
+                "west 26th",
+                "26th street",
+                "30 west 26th",
+                "west 26th street",
+            ]
+        );
+    }
+
+    #[test]
+    fn ngrams_bounds() {
+        let words = ["west", "street"];
+
+        // 0-grams don't make sense so `min` is at least 1.
+        assert_eq!(ngrams(&words, 0, 1), ["west", "street"]);
+        assert_eq!(ngrams(&words, 2, 10), ["west street"]);
+        assert!(ngrams(&words, 3, 4).is_empty());
+        assert!(ngrams(&words, 2, 1).is_empty());
+        assert!(ngrams(&[], 1, 3).is_empty());
+    }
+}
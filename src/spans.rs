@@ -0,0 +1,236 @@
+use std::ops::Range;
+
+use Token;
+
+/// A parsed component along with its location in the parser input.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SpannedComponent {
+    pub label: String,
+    /// The normalized value returned by libpostal, or the original text of the input if
+    /// `SpanOptions::original_text` was set and the component could be located.
+    pub value: String,
+    /// Byte range of the component in the input, if it could be located.
+    pub span: Option<Range<usize>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct SpanOptions {
+    /// Return the text of the input (with its original case) instead of the normalized value.
+    pub original_text: bool,
+}
+
+// Comparison key of a text: its letters and digits, lowercased.
+fn key(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Locates each `(label, value)` component in `input`, `tokens` being the tokens of `input`.
+///
+/// Components are looked up in order since libpostal keeps the token order, falling back to a
+/// search from the start of the input for out-of-order ones.
+pub(crate) fn align<'a, I>(
+    input: &str,
+    tokens: &[Token],
+    components: I,
+    options: SpanOptions,
+) -> Vec<SpannedComponent>
+where
+    I: Iterator<Item = (&'a str, &'a str)>,
+{
+    let tokens = tokens
+        .iter()
+        .filter(|t| !t.token_type.is_whitespace())
+        .map(|t| (t.range(), key(t.text(input))))
+        .filter(|(_, k)| !k.is_empty())
+        .collect::<Vec<_>>();
+    let mut cursor = 0;
+
+    components
+        .map(|(label, value)| {
+            let target = key(value);
+            let found = find(&tokens, &target, cursor).or_else(|| find(&tokens, &target, 0));
+            let span = found.map(|(start, end)| {
+                cursor = end + 1;
+                tokens[start].0.start..tokens[end].0.end
+            });
+            let value = match span {
+                Some(ref span) if options.original_text => input[span.clone()].to_owned(),
+                _ => value.to_owned(),
+            };
+            SpannedComponent {
+                label: label.to_owned(),
+                value,
+                span,
+            }
+        })
+        .collect()
+}
+
+// Returns the first and last token indexes of the first token sequence starting at `from` whose
+// keys, concatenated, are equal to `target`.
+fn find(tokens: &[(Range<usize>, String)], target: &str, from: usize) -> Option<(usize, usize)> {
+    if target.is_empty() {
+        return None;
+    }
+    for start in from..tokens.len() {
+        let mut acc = String::with_capacity(target.len());
+        for (end, (_, k)) in tokens.iter().enumerate().skip(start) {
+            acc.push_str(k);
+            if acc == target {
+                return Some((start, end));
+            }
+            if !target.starts_with(acc.as_str()) {
+                break;
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenType;
+
+    // Splits `input` the way libpostal roughly does: runs of alphanumeric characters, whitespace
+    // and single punctuation characters.
+    fn tokenize(input: &str) -> Vec<Token> {
+        let mut tokens: Vec<Token> = Vec::new();
+        for (offset, c) in input.char_indices() {
+            let token_type = if c.is_alphanumeric() {
+                TokenType::Word
+            } else if c.is_whitespace() {
+                TokenType::Whitespace
+            } else {
+                TokenType::Comma
+            };
+            match tokens.last_mut() {
+                Some(last) if last.token_type == token_type && token_type != TokenType::Comma => {
+                    last.len += c.len_utf8();
+                }
+                _ => tokens.push(Token {
+                    offset,
+                    len: c.len_utf8(),
+                    token_type,
+                }),
+            }
+        }
+        tokens
+    }
+
+    fn spans(
+        input: &str,
+        components: &[(&str, &str)],
+        options: SpanOptions,
+    ) -> Vec<SpannedComponent> {
+        align(input, &tokenize(input), components.iter().cloned(), options)
+    }
+
+    #[test]
+    fn in_order() {
+        let input = "30 W 26th St, New York";
+        let found = spans(
+            input,
+            &[
+                ("house_number", "30"),
+                ("road", "w 26th st"),
+                ("city", "new york"),
+            ],
+            SpanOptions::default(),
+        );
+
+        assert_eq!(
+            found.iter().map(|c| c.span.clone()).collect::<Vec<_>>(),
+            vec![Some(0..2), Some(3..12), Some(14..22)]
+        );
+        assert_eq!(found[1].label, "road");
+        assert_eq!(found[1].value, "w 26th st");
+    }
+
+    #[test]
+    fn out_of_order_components_are_searched_from_the_start() {
+        let input = "Paris 75001 France";
+        let found = spans(
+            input,
+            &[
+                ("postcode", "75001"),
+                ("city", "paris"),
+                ("country", "france"),
+            ],
+            SpanOptions::default(),
+        );
+
+        assert_eq!(
+            found.iter().map(|c| c.span.clone()).collect::<Vec<_>>(),
+            vec![Some(6..11), Some(0..5), Some(12..18)]
+        );
+    }
+
+    #[test]
+    fn repeated_words_are_found_after_the_previous_component() {
+        let input = "1 Main St, Main";
+        let found = spans(
+            input,
+            &[("house_number", "1"), ("road", "main st"), ("city", "main")],
+            SpanOptions::default(),
+        );
+
+        assert_eq!(found[2].span, Some(11..15));
+    }
+
+    #[test]
+    fn multi_token_components() {
+        // libpostal may drop the punctuation and join or split words.
+        let input = "Saint-Denis SW1A1AA";
+        let found = spans(
+            input,
+            &[("city", "saint denis"), ("postcode", "sw1a 1aa")],
+            SpanOptions::default(),
+        );
+
+        assert_eq!(found[0].span, Some(0..11));
+        assert_eq!(found[1].span, Some(12..19));
+    }
+
+    #[test]
+    fn original_text() {
+        let input = "30 W 26th St, NEW YORK";
+        let options = SpanOptions {
+            original_text: true,
+        };
+        let found = spans(
+            input,
+            &[("road", "w 26th st"), ("city", "new york")],
+            options,
+        );
+
+        assert_eq!(found[0].value, "W 26th St");
+        assert_eq!(found[1].value, "NEW YORK");
+    }
+
+    #[test]
+    fn unlocatable_components() {
+        let input = "30 W 26th St";
+        let options = SpanOptions {
+            original_text: true,
+        };
+        let found = spans(
+            input,
+            &[
+                ("road", "west 26th street"),
+                ("city", ""),
+                ("house_number", "30"),
+            ],
+            options,
+        );
+
+        assert_eq!(found[0].span, None);
+        // The normalized value is kept when the original text can't be found.
+        assert_eq!(found[0].value, "west 26th street");
+        assert_eq!(found[1].span, None);
+        assert_eq!(found[2].span, Some(0..2));
+    }
+}
@@ -19,6 +19,12 @@ extern "C" {
         values: *const *const c_char,
         num_languages: *mut size_t,
     ) -> *mut *mut c_char;
+    pub fn libpostal_classify_language(
+        address: *const c_char,
+    ) -> *mut libpostal_language_classifier_response_t;
+    pub fn libpostal_language_classifier_response_destroy(
+        this: *mut libpostal_language_classifier_response_t,
+    );
     pub fn libpostal_get_default_options() -> libpostal_normalize_options_t;
     pub fn libpostal_expand_address(
         input: *const c_char,
@@ -172,11 +178,48 @@ pub const LIBPOSTAL_ADDRESS_ENTRANCE: u16 = (1 << 7);
 pub const LIBPOSTAL_ADDRESS_CATEGORY: u16 = (1 << 8);
 pub const LIBPOSTAL_ADDRESS_NEAR: u16 = (1 << 9);
 
+// Bits 10 to 12 aren't used by libpostal.
+pub const LIBPOSTAL_ADDRESS_RESERVED: u16 = (1 << 10) | (1 << 11) | (1 << 12);
+
 pub const LIBPOSTAL_ADDRESS_TOPONYM: u16 = (1 << 13);
 pub const LIBPOSTAL_ADDRESS_POSTAL_CODE: u16 = (1 << 14);
 pub const LIBPOSTAL_ADDRESS_PO_BOX: u16 = (1 << 15);
 pub const LIBPOSTAL_ADDRESS_ALL: u16 = ((1u32 << 16) - 1) as u16;
 
+pub const LIBPOSTAL_NORMALIZE_STRING_LATIN_ASCII: u64 = 1 << 0;
+pub const LIBPOSTAL_NORMALIZE_STRING_TRANSLITERATE: u64 = 1 << 1;
+pub const LIBPOSTAL_NORMALIZE_STRING_STRIP_ACCENTS: u64 = 1 << 2;
+pub const LIBPOSTAL_NORMALIZE_STRING_DECOMPOSE: u64 = 1 << 3;
+pub const LIBPOSTAL_NORMALIZE_STRING_LOWERCASE: u64 = 1 << 4;
+pub const LIBPOSTAL_NORMALIZE_STRING_TRIM: u64 = 1 << 5;
+pub const LIBPOSTAL_NORMALIZE_STRING_REPLACE_HYPHENS: u64 = 1 << 6;
+pub const LIBPOSTAL_NORMALIZE_STRING_COMPOSE: u64 = 1 << 7;
+pub const LIBPOSTAL_NORMALIZE_STRING_SIMPLE_LATIN_ASCII: u64 = 1 << 8;
+pub const LIBPOSTAL_NORMALIZE_STRING_REPLACE_NUMEX: u64 = 1 << 9;
+
+pub const LIBPOSTAL_NORMALIZE_TOKEN_REPLACE_HYPHENS: u64 = 1 << 0;
+pub const LIBPOSTAL_NORMALIZE_TOKEN_DELETE_HYPHENS: u64 = 1 << 1;
+pub const LIBPOSTAL_NORMALIZE_TOKEN_DELETE_FINAL_PERIOD: u64 = 1 << 2;
+pub const LIBPOSTAL_NORMALIZE_TOKEN_DELETE_ACRONYM_PERIODS: u64 = 1 << 3;
+pub const LIBPOSTAL_NORMALIZE_TOKEN_DROP_ENGLISH_POSSESSIVES: u64 = 1 << 4;
+pub const LIBPOSTAL_NORMALIZE_TOKEN_DELETE_OTHER_APOSTROPHE: u64 = 1 << 5;
+pub const LIBPOSTAL_NORMALIZE_TOKEN_SPLIT_ALPHA_FROM_NUMERIC: u64 = 1 << 6;
+pub const LIBPOSTAL_NORMALIZE_TOKEN_REPLACE_DIGITS: u64 = 1 << 7;
+pub const LIBPOSTAL_NORMALIZE_TOKEN_REPLACE_NUMERIC_TOKEN_LETTERS: u64 = 1 << 8;
+pub const LIBPOSTAL_NORMALIZE_TOKEN_REPLACE_NUMERIC_HYPHENS: u64 = 1 << 9;
+
+pub const LIBPOSTAL_NORMALIZE_DEFAULT_STRING_OPTIONS: u64 = LIBPOSTAL_NORMALIZE_STRING_LATIN_ASCII
+    | LIBPOSTAL_NORMALIZE_STRING_COMPOSE
+    | LIBPOSTAL_NORMALIZE_STRING_TRIM
+    | LIBPOSTAL_NORMALIZE_STRING_REPLACE_HYPHENS
+    | LIBPOSTAL_NORMALIZE_STRING_STRIP_ACCENTS
+    | LIBPOSTAL_NORMALIZE_STRING_LOWERCASE;
+pub const LIBPOSTAL_NORMALIZE_DEFAULT_TOKEN_OPTIONS: u64 = LIBPOSTAL_NORMALIZE_TOKEN_REPLACE_HYPHENS
+    | LIBPOSTAL_NORMALIZE_TOKEN_DELETE_FINAL_PERIOD
+    | LIBPOSTAL_NORMALIZE_TOKEN_DELETE_ACRONYM_PERIODS
+    | LIBPOSTAL_NORMALIZE_TOKEN_DROP_ENGLISH_POSSESSIVES
+    | LIBPOSTAL_NORMALIZE_TOKEN_DELETE_OTHER_APOSTROPHE;
+
 #[derive(Debug)]
 #[repr(C)]
 pub struct libpostal_normalize_options_t {
@@ -203,6 +246,14 @@ pub struct libpostal_normalize_options_t {
     pub roman_numerals: c_bool,
 }
 
+#[derive(Debug)]
+#[repr(C)]
+pub struct libpostal_language_classifier_response_t {
+    pub num_languages: size_t,
+    pub languages: *mut *mut c_char,
+    pub probs: *mut c_double,
+}
+
 #[derive(Debug)]
 #[repr(C)]
 pub struct libpostal_address_parser_options_t {
@@ -0,0 +1,278 @@
+//! [Tantivy](https://github.com/quickwit-oss/tantivy) tokenizer emitting libpostal normalized
+//! tokens.
+//!
+//! ```ignore
+//! let core = Arc::new(Core::setup().expect("failed to setup libpostal"));
+//! index
+//!     .tokenizers()
+//!     .register("postal", PostalTokenizer::new(core).with_expansions(true));
+//! ```
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
+
+use sys;
+use Core;
+use NormalizeOptions;
+
+/// Tokenizer emitting libpostal normalized tokens.
+///
+/// If `with_expansions` is enabled, the words of the `Core::expand_address` variants are emitted
+/// as synonyms at the same position as the token they replace. Only the expansions having the
+/// same number of words as the input are used since the others can't be aligned.
+#[derive(Clone)]
+pub struct PostalTokenizer {
+    core: Arc<Core>,
+    string_options: u64,
+    token_options: u64,
+    expansions: bool,
+    normalize_options: NormalizeOptions,
+}
+
+impl PostalTokenizer {
+    pub fn new(core: Arc<Core>) -> PostalTokenizer {
+        PostalTokenizer {
+            core,
+            string_options: sys::LIBPOSTAL_NORMALIZE_DEFAULT_STRING_OPTIONS,
+            token_options: sys::LIBPOSTAL_NORMALIZE_DEFAULT_TOKEN_OPTIONS,
+            expansions: false,
+            normalize_options: NormalizeOptions::default(),
+        }
+    }
+
+    /// Sets the `sys::LIBPOSTAL_NORMALIZE_STRING_*` flags used to normalize tokens.
+    pub fn with_string_options(mut self, string_options: u64) -> PostalTokenizer {
+        self.string_options = string_options;
+        self
+    }
+
+    /// Sets the `sys::LIBPOSTAL_NORMALIZE_TOKEN_*` flags used to normalize tokens.
+    pub fn with_token_options(mut self, token_options: u64) -> PostalTokenizer {
+        self.token_options = token_options;
+        self
+    }
+
+    pub fn with_expansions(mut self, expansions: bool) -> PostalTokenizer {
+        self.expansions = expansions;
+        self
+    }
+
+    /// Sets the options given to `Core::expand_address` when `with_expansions` is enabled.
+    pub fn with_normalize_options(mut self, options: NormalizeOptions) -> PostalTokenizer {
+        self.normalize_options = options;
+        self
+    }
+
+    fn tokens(&self, text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+
+        for original in self.core.tokenize(text, false) {
+            if original.token_type.is_punctuation() || original.token_type.is_whitespace() {
+                continue;
+            }
+            let range = original.range();
+            let normalized = self.core.normalized_tokens(
+                original.text(text),
+                self.string_options,
+                self.token_options,
+                false,
+            );
+            // Offsets are taken from the input token so highlighting still works even though
+            // the normalized text may have a different length.
+            for value in normalized {
+                if value.token.token_type.is_punctuation() || value.value.is_empty() {
+                    continue;
+                }
+                tokens.push(Token {
+                    offset_from: range.start,
+                    offset_to: range.end,
+                    position: tokens.len(),
+                    text: value.value,
+                    position_length: 1,
+                });
+            }
+        }
+        if self.expansions && !tokens.is_empty() {
+            self.add_expansions(text, &mut tokens);
+        }
+        tokens
+    }
+
+    fn add_expansions(&self, text: &str, tokens: &mut Vec<Token>) {
+        let expansions = self
+            .core
+            .expand_address(text, self.normalize_options.clone())
+            .into_iter()
+            .map(|expansion| {
+                self.core
+                    .tokenize(&expansion, false)
+                    .into_iter()
+                    .filter(|t| !t.token_type.is_punctuation() && !t.token_type.is_whitespace())
+                    .map(|t| t.text(&expansion).to_owned())
+                    .collect::<Vec<_>>()
+            });
+        add_synonyms(tokens, expansions);
+    }
+}
+
+// Adds the words of each expansion as synonyms of the token at the same position, skipping the
+// expansions which don't have as many words as there are tokens.
+fn add_synonyms<I: IntoIterator<Item = Vec<String>>>(tokens: &mut Vec<Token>, expansions: I) {
+    let words_count = tokens.len();
+    let mut seen = tokens
+        .iter()
+        .map(|t| (t.position, t.text.clone()))
+        .collect::<HashSet<_>>();
+    let mut synonyms = Vec::new();
+
+    for words in expansions {
+        if words.len() != words_count {
+            continue;
+        }
+        for (position, word) in words.into_iter().enumerate() {
+            if seen.insert((position, word.clone())) {
+                let original = &tokens[position];
+                synonyms.push(Token {
+                    offset_from: original.offset_from,
+                    offset_to: original.offset_to,
+                    position,
+                    text: word,
+                    position_length: 1,
+                });
+            }
+        }
+    }
+    tokens.extend(synonyms);
+    // Tantivy expects positions to never go backward.
+    tokens.sort_by_key(|t| t.position);
+}
+
+impl Tokenizer for PostalTokenizer {
+    type TokenStream<'a> = PostalTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> PostalTokenStream {
+        PostalTokenStream {
+            tokens: self.tokens(text),
+            pos: None,
+        }
+    }
+}
+
+pub struct PostalTokenStream {
+    tokens: Vec<Token>,
+    pos: Option<usize>,
+}
+
+impl TokenStream for PostalTokenStream {
+    fn advance(&mut self) -> bool {
+        let next = self.pos.map(|p| p + 1).unwrap_or(0);
+        self.pos = Some(next);
+        next < self.tokens.len()
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.pos.expect("advance must be called first")]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.pos.expect("advance must be called first")]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(position: usize, offsets: (usize, usize), text: &str) -> Token {
+        Token {
+            offset_from: offsets.0,
+            offset_to: offsets.1,
+            position,
+            text: text.to_owned(),
+            position_length: 1,
+        }
+    }
+
+    // Tokens of "30 W 26th St".
+    fn tokens() -> Vec<Token> {
+        vec![
+            token(0, (0, 2), "30"),
+            token(1, (3, 4), "w"),
+            token(2, (5, 9), "26th"),
+            token(3, (10, 12), "st"),
+        ]
+    }
+
+    fn words(s: &str) -> Vec<String> {
+        s.split(' ').map(|w| w.to_owned()).collect()
+    }
+
+    fn summary(tokens: &[Token]) -> Vec<(usize, &str, usize, usize)> {
+        tokens
+            .iter()
+            .map(|t| (t.position, t.text.as_str(), t.offset_from, t.offset_to))
+            .collect()
+    }
+
+    #[test]
+    fn synonyms_take_the_place_of_the_original_tokens() {
+        let mut tokens = tokens();
+        add_synonyms(
+            &mut tokens,
+            vec![words("30 w 26th st"), words("30 west 26th street")],
+        );
+
+        assert_eq!(
+            summary(&tokens),
+            [
+                (0, "30", 0, 2),
+                (1, "w", 3, 4),
+                (1, "west", 3, 4),
+                (2, "26th", 5, 9),
+                (3, "st", 10, 12),
+                (3, "street", 10, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn expansions_with_another_word_count_are_skipped() {
+        let mut tokens = tokens();
+        add_synonyms(
+            &mut tokens,
+            vec![
+                words("30 west twenty sixth street"),
+                words("30 w 26th"),
+                words("30 w 26th saint"),
+            ],
+        );
+
+        assert_eq!(
+            summary(&tokens),
+            [
+                (0, "30", 0, 2),
+                (1, "w", 3, 4),
+                (2, "26th", 5, 9),
+                (3, "st", 10, 12),
+                (3, "saint", 10, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn token_stream() {
+        let mut stream = PostalTokenStream {
+            tokens: tokens(),
+            pos: None,
+        };
+        let mut texts = Vec::new();
+
+        while stream.advance() {
+            texts.push(stream.token().text.clone());
+        }
+        assert_eq!(texts, ["30", "w", "26th", "st"]);
+        assert!(!stream.advance());
+    }
+}
@@ -0,0 +1,347 @@
+use std::ops::Range;
+
+use sys;
+use traits::{ToC, ToRust};
+
+use libc::free;
+
+use Core;
+
+/// Type of a token, as returned by the libpostal tokenizer.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TokenType {
+    Word,
+    Abbreviation,
+    IdeographicChar,
+    HangulSyllable,
+    Acronym,
+    Phrase,
+    Email,
+    Url,
+    UsPhone,
+    IntlPhone,
+    Numeric,
+    Ordinal,
+    RomanNumeral,
+    IdeographicNumber,
+    Period,
+    Exclamation,
+    QuestionMark,
+    Comma,
+    Colon,
+    Semicolon,
+    Plus,
+    Ampersand,
+    AtSign,
+    Pound,
+    Ellipsis,
+    Dash,
+    BreakingDash,
+    Hyphen,
+    PunctOpen,
+    PunctClose,
+    DoubleQuote,
+    SingleQuote,
+    OpenQuote,
+    CloseQuote,
+    Slash,
+    Backslash,
+    GreaterThan,
+    LessThan,
+    /// Any other character which isn't a letter, a digit or whitespace.
+    Other,
+    Whitespace,
+    Newline,
+    InvalidChar,
+    Unknown(u16),
+}
+
+impl TokenType {
+    // The values are the ones of libpostal's `token_types.h`.
+    pub(crate) fn from_c(value: u16) -> TokenType {
+        match value {
+            1 => TokenType::Word,
+            2 => TokenType::Abbreviation,
+            3 => TokenType::IdeographicChar,
+            4 => TokenType::HangulSyllable,
+            5 => TokenType::Acronym,
+            10 => TokenType::Phrase,
+            20 => TokenType::Email,
+            21 => TokenType::Url,
+            22 => TokenType::UsPhone,
+            23 => TokenType::IntlPhone,
+            50 => TokenType::Numeric,
+            51 => TokenType::Ordinal,
+            52 => TokenType::RomanNumeral,
+            53 => TokenType::IdeographicNumber,
+            100 => TokenType::Period,
+            101 => TokenType::Exclamation,
+            102 => TokenType::QuestionMark,
+            103 => TokenType::Comma,
+            104 => TokenType::Colon,
+            105 => TokenType::Semicolon,
+            106 => TokenType::Plus,
+            107 => TokenType::Ampersand,
+            108 => TokenType::AtSign,
+            109 => TokenType::Pound,
+            110 => TokenType::Ellipsis,
+            111 => TokenType::Dash,
+            112 => TokenType::BreakingDash,
+            113 => TokenType::Hyphen,
+            114 => TokenType::PunctOpen,
+            115 => TokenType::PunctClose,
+            119 => TokenType::DoubleQuote,
+            120 => TokenType::SingleQuote,
+            121 => TokenType::OpenQuote,
+            122 => TokenType::CloseQuote,
+            124 => TokenType::Slash,
+            125 => TokenType::Backslash,
+            126 => TokenType::GreaterThan,
+            127 => TokenType::LessThan,
+            200 => TokenType::Other,
+            300 => TokenType::Whitespace,
+            301 => TokenType::Newline,
+            500 => TokenType::InvalidChar,
+            x => TokenType::Unknown(x),
+        }
+    }
+
+    pub fn is_word(&self) -> bool {
+        matches!(
+            *self,
+            TokenType::Word
+                | TokenType::Abbreviation
+                | TokenType::IdeographicChar
+                | TokenType::HangulSyllable
+                | TokenType::Acronym
+                | TokenType::Phrase
+        )
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            *self,
+            TokenType::Numeric
+                | TokenType::Ordinal
+                | TokenType::RomanNumeral
+                | TokenType::IdeographicNumber
+        )
+    }
+
+    pub fn is_punctuation(&self) -> bool {
+        matches!(
+            *self,
+            TokenType::Period
+                | TokenType::Exclamation
+                | TokenType::QuestionMark
+                | TokenType::Comma
+                | TokenType::Colon
+                | TokenType::Semicolon
+                | TokenType::Plus
+                | TokenType::Ampersand
+                | TokenType::AtSign
+                | TokenType::Pound
+                | TokenType::Ellipsis
+                | TokenType::Dash
+                | TokenType::BreakingDash
+                | TokenType::Hyphen
+                | TokenType::PunctOpen
+                | TokenType::PunctClose
+                | TokenType::DoubleQuote
+                | TokenType::SingleQuote
+                | TokenType::OpenQuote
+                | TokenType::CloseQuote
+                | TokenType::Slash
+                | TokenType::Backslash
+                | TokenType::GreaterThan
+                | TokenType::LessThan
+        )
+    }
+
+    pub fn is_whitespace(&self) -> bool {
+        *self == TokenType::Whitespace || *self == TokenType::Newline
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Token {
+    /// Byte offset of the token in the input.
+    pub offset: usize,
+    /// Length of the token in bytes.
+    pub len: usize,
+    pub token_type: TokenType,
+}
+
+impl Token {
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.len
+    }
+
+    /// Returns the text of the token in `input`, which must be the string which was tokenized.
+    pub fn text<'a>(&self, input: &'a str) -> &'a str {
+        input.get(self.range()).unwrap_or("")
+    }
+}
+
+/// A token returned by `Core::normalized_tokens`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct NormalizedToken {
+    pub value: String,
+    /// Offsets of this token are relative to the normalized string, not the input.
+    pub token: Token,
+}
+
+impl Core {
+    /// Splits `input` into tokens. If `whitespace` is `true`, whitespace tokens are returned as
+    /// well.
+    pub fn tokenize(&self, input: &str, whitespace: bool) -> Vec<Token> {
+        let c_input = input.to_c();
+        let mut size = 0;
+
+        let ptr =
+            unsafe { sys::libpostal_tokenize(c_input.as_ptr(), whitespace.to_c(), &mut size) };
+        if ptr.is_null() {
+            return Vec::new();
+        }
+        let mut ret = Vec::with_capacity(size);
+        unsafe {
+            for pos in 0..size {
+                let token = &*ptr.add(pos);
+                ret.push(Token {
+                    offset: token.offset,
+                    len: token.len,
+                    token_type: TokenType::from_c(token.type_),
+                });
+            }
+            free(ptr as _);
+        }
+        ret
+    }
+
+    /// Normalizes and tokenizes `input`. `string_options` and `token_options` are combinations
+    /// of the `sys::LIBPOSTAL_NORMALIZE_STRING_*` and `sys::LIBPOSTAL_NORMALIZE_TOKEN_*` flags.
+    pub fn normalized_tokens(
+        &self,
+        input: &str,
+        string_options: u64,
+        token_options: u64,
+        whitespace: bool,
+    ) -> Vec<NormalizedToken> {
+        let c_input = input.to_c();
+        let mut size = 0;
+
+        let ptr = unsafe {
+            sys::libpostal_normalized_tokens(
+                c_input.as_ptr(),
+                string_options,
+                token_options,
+                whitespace.to_c(),
+                &mut size,
+            )
+        };
+        if ptr.is_null() {
+            return Vec::new();
+        }
+        let mut ret = Vec::with_capacity(size);
+        unsafe {
+            for pos in 0..size {
+                let token = &*ptr.add(pos);
+                ret.push(NormalizedToken {
+                    value: token.str.to_rust(),
+                    token: Token {
+                        offset: token.token.offset,
+                        len: token.token.len,
+                        token_type: TokenType::from_c(token.token.type_),
+                    },
+                });
+                free(token.str as _);
+            }
+            free(ptr as _);
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_types_match_libpostal() {
+        // From libpostal's `token_types.h`.
+        let table = [
+            (1, TokenType::Word),
+            (2, TokenType::Abbreviation),
+            (3, TokenType::IdeographicChar),
+            (4, TokenType::HangulSyllable),
+            (5, TokenType::Acronym),
+            (10, TokenType::Phrase),
+            (20, TokenType::Email),
+            (21, TokenType::Url),
+            (22, TokenType::UsPhone),
+            (23, TokenType::IntlPhone),
+            (50, TokenType::Numeric),
+            (51, TokenType::Ordinal),
+            (52, TokenType::RomanNumeral),
+            (53, TokenType::IdeographicNumber),
+            (100, TokenType::Period),
+            (101, TokenType::Exclamation),
+            (102, TokenType::QuestionMark),
+            (103, TokenType::Comma),
+            (104, TokenType::Colon),
+            (105, TokenType::Semicolon),
+            (106, TokenType::Plus),
+            (107, TokenType::Ampersand),
+            (108, TokenType::AtSign),
+            (109, TokenType::Pound),
+            (110, TokenType::Ellipsis),
+            (111, TokenType::Dash),
+            (112, TokenType::BreakingDash),
+            (113, TokenType::Hyphen),
+            (114, TokenType::PunctOpen),
+            (115, TokenType::PunctClose),
+            (119, TokenType::DoubleQuote),
+            (120, TokenType::SingleQuote),
+            (121, TokenType::OpenQuote),
+            (122, TokenType::CloseQuote),
+            (124, TokenType::Slash),
+            (125, TokenType::Backslash),
+            (126, TokenType::GreaterThan),
+            (127, TokenType::LessThan),
+            (200, TokenType::Other),
+            (300, TokenType::Whitespace),
+            (301, TokenType::Newline),
+            (500, TokenType::InvalidChar),
+        ];
+
+        for &(value, token_type) in &table {
+            assert_eq!(TokenType::from_c(value), token_type, "{}", value);
+        }
+        for value in 0..=600 {
+            if table.iter().all(|&(v, _)| v != value) {
+                assert_eq!(TokenType::from_c(value), TokenType::Unknown(value));
+            }
+        }
+    }
+
+    #[test]
+    fn categories() {
+        // libpostal's punctuation types are the ones in [PERIOD, OTHER).
+        for value in 0..=600 {
+            let token_type = TokenType::from_c(value);
+            assert_eq!(
+                token_type.is_punctuation(),
+                (100..200).contains(&value) && token_type != TokenType::Unknown(value),
+                "{:?}",
+                token_type
+            );
+        }
+        assert!(TokenType::Word.is_word());
+        assert!(TokenType::Phrase.is_word());
+        assert!(!TokenType::Numeric.is_word());
+        assert!(TokenType::Ordinal.is_numeric());
+        assert!(!TokenType::Word.is_numeric());
+        assert!(TokenType::Newline.is_whitespace());
+        assert!(!TokenType::Other.is_whitespace());
+    }
+}
@@ -101,7 +101,7 @@ impl ToC for str {
     }
 }
 
-impl<'a> ToC for &'a [String] {
+impl<S: AsRef<str>> ToC for &[S] {
     type Out = (Vec<CString>, Vec<*const c_char>);
 
     #[inline]
@@ -110,7 +110,7 @@ impl<'a> ToC for &'a [String] {
         let mut c_strs = Vec::with_capacity(self.len());
 
         for s in self.iter() {
-            let cs = CString::new(s.as_str()).expect("unexpected '\0' in string");
+            let cs = CString::new(s.as_ref()).expect("unexpected '\0' in string");
             let ptr = cs.as_ptr();
 
             strs.push(cs);
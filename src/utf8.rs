@@ -0,0 +1,109 @@
+use std::error::Error;
+use std::ffi::{CStr, CString, IntoStringError};
+use std::fmt;
+use std::str::Utf8Error;
+
+/// Returned by the `*_strict` functions when libpostal returned a string which isn't valid
+/// UTF-8, instead of replacing the invalid bytes with `U+FFFD` like the other functions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LossyConversion {
+    bytes: Vec<u8>,
+    error: Utf8Error,
+}
+
+impl LossyConversion {
+    /// Returns the string returned by libpostal.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.error
+    }
+}
+
+impl fmt::Display for LossyConversion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "libpostal returned invalid UTF-8 ({}): {:?}",
+            self.error,
+            String::from_utf8_lossy(&self.bytes)
+        )
+    }
+}
+
+impl Error for LossyConversion {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<IntoStringError> for LossyConversion {
+    fn from(e: IntoStringError) -> LossyConversion {
+        LossyConversion {
+            error: e.utf8_error(),
+            bytes: e.into_cstring().into_bytes(),
+        }
+    }
+}
+
+pub(crate) fn check_strict(s: &CStr) -> Result<(), LossyConversion> {
+    match s.to_str() {
+        Ok(_) => Ok(()),
+        Err(error) => Err(LossyConversion {
+            bytes: s.to_bytes().to_vec(),
+            error,
+        }),
+    }
+}
+
+pub(crate) fn into_string_strict(s: CString) -> Result<String, LossyConversion> {
+    Ok(s.into_string()?)
+}
+
+pub(crate) fn into_string_lossy(s: CString) -> String {
+    match s.into_string() {
+        Ok(s) => s,
+        Err(e) => String::from_utf8_lossy(e.into_cstring().as_bytes()).into_owned(),
+    }
+}
+
+/// Replaces the invalid UTF-8 of `s`, without copying it if it's valid.
+pub(crate) fn cstring_lossy(s: CString) -> CString {
+    if s.to_str().is_ok() {
+        return s;
+    }
+    CString::new(String::from_utf8_lossy(s.as_bytes()).into_owned())
+        .expect("replaced string has no '\0'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid() {
+        let s = CString::new("café").unwrap();
+        assert_eq!(check_strict(&s), Ok(()));
+        assert_eq!(into_string_strict(s.clone()), Ok("café".to_owned()));
+        assert_eq!(into_string_lossy(s.clone()), "café");
+        assert_eq!(cstring_lossy(s.clone()), s);
+    }
+
+    #[test]
+    fn invalid() {
+        let s = CString::new(&b"caf\xe9"[..]).unwrap();
+        let error = check_strict(&s).unwrap_err();
+        assert_eq!(error.as_bytes(), b"caf\xe9");
+        assert_eq!(error.utf8_error().valid_up_to(), 3);
+        assert_eq!(into_string_strict(s.clone()), Err(error.clone()));
+        assert_eq!(into_string_lossy(s.clone()), "caf\u{fffd}");
+        assert_eq!(cstring_lossy(s).to_str(), Ok("caf\u{fffd}"));
+        assert_eq!(error.into_bytes(), b"caf\xe9");
+    }
+}
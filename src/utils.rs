@@ -1,3 +1,5 @@
+use std::ffi::{CStr, CString};
+
 use traits::ToRust;
 
 use libc::c_char;
@@ -16,3 +18,21 @@ pub(crate) fn ptr_to_rust(ptr: *mut *mut c_char, len: usize) -> Vec<String> {
     }
     ret
 }
+
+/// Same as `ptr_to_rust` but copies the strings as is, without any UTF-8 conversion.
+pub(crate) fn ptr_to_cstrings(ptr: *mut *mut c_char, len: usize) -> Vec<CString> {
+    if ptr.is_null() || len == 0 {
+        return Vec::new();
+    }
+    (0..len)
+        .map(|pos| unsafe { ptr_to_cstring(*ptr.add(pos)) })
+        .collect()
+}
+
+/// Copies the string `ptr` points to, or returns an empty one if it's null.
+pub(crate) unsafe fn ptr_to_cstring(ptr: *const c_char) -> CString {
+    if ptr.is_null() {
+        return CString::default();
+    }
+    CStr::from_ptr(ptr).to_owned()
+}
@@ -0,0 +1,87 @@
+extern crate rpostal;
+
+mod common;
+
+use rpostal::{AddressParserOptions, ParseHints};
+
+fn label_of(address: &str, value: &str, hints: ParseHints) -> Option<String> {
+    common::parser()
+        .parse_address_with(address, hints)
+        .expect("parse failed")
+        .into_iter()
+        .find(|a| a.value.to_str() == Ok(value))
+        .map(|a| a.label.into_string().unwrap())
+}
+
+// Regression test: the country hint used to be built from the language hint, so the country
+// given was never passed to libpostal.
+#[test]
+fn country_hint_changes_parse() {
+    // "Ontario" is a Canadian province but also a city of California.
+    let address = "100 main street ontario";
+    let canada = label_of(
+        address,
+        "ontario",
+        ParseHints {
+            country: Some("ca"),
+            ..ParseHints::default()
+        },
+    );
+    let usa = label_of(
+        address,
+        "ontario",
+        ParseHints {
+            country: Some("us"),
+            ..ParseHints::default()
+        },
+    );
+    assert_eq!(canada.as_deref(), Some("state"));
+    assert_eq!(usa.as_deref(), Some("city"));
+}
+
+#[test]
+fn country_hint_isnt_the_language() {
+    let canada = label_of(
+        "100 main street ontario",
+        "ontario",
+        ParseHints {
+            language: Some("en"),
+            country: Some("ca"),
+        },
+    );
+    assert_eq!(canada.as_deref(), Some("state"));
+}
+
+#[test]
+fn hints_are_kept_alive_across_calls() {
+    let parser = common::parser();
+    let mut options = AddressParserOptions::new();
+    options.set_language(Some("en"));
+    options.set_country(Some("us"));
+    let expected = parser.parse_address("781 franklin ave brooklyn ny 11216", &options);
+    for _ in 0..100 {
+        assert_eq!(
+            parser.parse_address("781 franklin ave brooklyn ny 11216", &options),
+            expected
+        );
+    }
+}
+
+#[test]
+fn spanned_parse_uses_hints() {
+    let parser = common::parser();
+    let address = "100 main street ontario";
+    let spanned = |country| {
+        let options = AddressParserOptions::from(ParseHints {
+            country: Some(country),
+            ..ParseHints::default()
+        });
+        parser
+            .parse_address_spanned(address, &options, Default::default())
+            .expect("parse failed")
+            .into_iter()
+            .map(|c| c.label)
+            .collect::<Vec<_>>()
+    };
+    assert_ne!(spanned("ca"), spanned("us"));
+}
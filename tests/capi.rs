@@ -0,0 +1,236 @@
+//! Calls the C API the way a C program would. Needs the libpostal models to be installed, except
+//! for the tests which don't create a handle.
+
+#![cfg(feature = "capi")]
+
+extern crate libc;
+extern crate rpostal;
+extern crate serde_json;
+
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use rpostal::capi::*;
+use serde_json::{json, Value};
+
+fn c(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+fn last_error() -> Option<String> {
+    let error = rpostal_last_error();
+    if error.is_null() {
+        return None;
+    }
+    Some(
+        unsafe { CStr::from_ptr(error) }
+            .to_str()
+            .unwrap()
+            .to_owned(),
+    )
+}
+
+/// Takes ownership of a string returned by the library and parses it.
+fn take(s: *mut libc::c_char) -> Result<Value, String> {
+    if s.is_null() {
+        return Err(last_error().expect("NULL returned without error"));
+    }
+    let json = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_owned();
+    unsafe { rpostal_string_free(s) };
+    Ok(serde_json::from_str(&json).unwrap())
+}
+
+struct Handle(*mut RpostalHandle);
+
+impl Handle {
+    fn new() -> Handle {
+        let handle = unsafe { rpostal_new(ptr::null()) };
+        assert!(!handle.is_null(), "{:?}", last_error());
+        Handle(handle)
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        unsafe { rpostal_free(self.0) };
+    }
+}
+
+#[test]
+fn version() {
+    assert_eq!(rpostal_capi_version() >> 16, RPOSTAL_CAPI_VERSION_MAJOR);
+    assert_eq!(rpostal_capi_version() & 0xffff, RPOSTAL_CAPI_VERSION_MINOR);
+}
+
+#[test]
+fn header_is_up_to_date() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/rpostal.h"));
+    let committed = include_str!("../include/rpostal.h");
+    assert!(
+        generated == committed,
+        "include/rpostal.h is outdated, copy it from {}",
+        concat!(env!("OUT_DIR"), "/rpostal.h")
+    );
+}
+
+#[test]
+fn null_arguments() {
+    let address = c("30 W 26th St");
+    unsafe {
+        assert_eq!(
+            take(rpostal_parse(ptr::null(), address.as_ptr(), ptr::null())),
+            Err("null handle".to_owned())
+        );
+        assert_eq!(
+            take(rpostal_expand(ptr::null(), address.as_ptr(), ptr::null())),
+            Err("null handle".to_owned())
+        );
+        assert_eq!(
+            take(rpostal_near_dupe_hashes(
+                ptr::null(),
+                ptr::null(),
+                ptr::null()
+            )),
+            Err("null handle".to_owned())
+        );
+        assert!(!rpostal_set_cache_capacity(ptr::null(), 0));
+        assert_eq!(last_error().as_deref(), Some("null handle"));
+        // Freeing `NULL` does nothing.
+        rpostal_free(ptr::null_mut());
+        rpostal_string_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn last_error_is_per_thread() {
+    unsafe { rpostal_expand(ptr::null(), ptr::null(), ptr::null()) };
+    assert_eq!(last_error().as_deref(), Some("null handle"));
+    std::thread::spawn(|| assert_eq!(last_error(), None))
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn parse() {
+    let handle = Handle::new();
+    let address = c("781 Franklin Ave Crown Heights Brooklyn NY 11216");
+    let options = c(r#"{"country": "US"}"#);
+    let parsed =
+        take(unsafe { rpostal_parse(handle.0, address.as_ptr(), options.as_ptr()) }).unwrap();
+    assert!(parsed["components"]
+        .as_array()
+        .unwrap()
+        .contains(&json!({"label": "road", "value": "franklin ave"})));
+    assert!(parsed["quality"]["score"].is_number());
+    assert_eq!(last_error(), None);
+
+    let invalid = c(r#"{"country": 1}"#);
+    let error =
+        take(unsafe { rpostal_parse(handle.0, address.as_ptr(), invalid.as_ptr()) }).unwrap_err();
+    assert!(error.starts_with("invalid options"), "{}", error);
+    let error = take(unsafe { rpostal_parse(handle.0, ptr::null(), ptr::null()) }).unwrap_err();
+    assert_eq!(error, "null `address`");
+}
+
+#[test]
+fn parse_cache() {
+    let handle = Handle::new();
+    let address = c("781 Franklin Ave Crown Heights Brooklyn NY 11216");
+    let parse = || take(unsafe { rpostal_parse(handle.0, address.as_ptr(), ptr::null()) });
+
+    let parsed = parse().unwrap();
+    assert_eq!(parse().unwrap(), parsed);
+    assert!(unsafe { rpostal_set_cache_capacity(handle.0, 0) });
+    assert_eq!(parse().unwrap(), parsed);
+}
+
+#[test]
+fn expand() {
+    let handle = Handle::new();
+    let address = c("30 W 26th St");
+    let expansions =
+        take(unsafe { rpostal_expand(handle.0, address.as_ptr(), ptr::null()) }).unwrap();
+    assert!(expansions
+        .as_array()
+        .unwrap()
+        .contains(&json!("30 west 26th street")));
+
+    let options = c(r#"{"languages": ["en"]}"#);
+    let roots =
+        take(unsafe { rpostal_expand_root(handle.0, address.as_ptr(), options.as_ptr()) }).unwrap();
+    assert!(!roots.as_array().unwrap().is_empty());
+
+    let unknown = c(r#"{"languages": ["xx"]}"#);
+    let error =
+        take(unsafe { rpostal_expand(handle.0, address.as_ptr(), unknown.as_ptr()) }).unwrap_err();
+    assert!(error.contains("unknown language"), "{}", error);
+}
+
+#[test]
+fn dedupe() {
+    let handle = Handle::new();
+    let components = c(r#"[
+        {"label": "house_number", "value": "30"},
+        {"label": "road", "value": "w 26th st"},
+        {"label": "city", "value": "new york"}
+    ]"#);
+    let hashes =
+        take(unsafe { rpostal_near_dupe_hashes(handle.0, components.as_ptr(), ptr::null()) })
+            .unwrap();
+    assert!(!hashes.as_array().unwrap().is_empty());
+
+    let options = c(r#"{"with_latlon": true, "latitude": 40.74, "longitude": -73.99}"#);
+    let with_latlon =
+        take(unsafe { rpostal_near_dupe_hashes(handle.0, components.as_ptr(), options.as_ptr()) })
+            .unwrap();
+    assert_ne!(with_latlon, hashes);
+    let invalid = c(r#"{"with_latlon": true, "latitude": 91, "longitude": 0}"#);
+    let error =
+        take(unsafe { rpostal_near_dupe_hashes(handle.0, components.as_ptr(), invalid.as_ptr()) })
+            .unwrap_err();
+    assert!(error.starts_with("invalid latitude"), "{}", error);
+
+    let (street, a, b) = (c("street"), c("W 26th St"), c("West 26th Street"));
+    let status = take(unsafe {
+        rpostal_is_duplicate(
+            handle.0,
+            street.as_ptr(),
+            a.as_ptr(),
+            b.as_ptr(),
+            ptr::null(),
+        )
+    })
+    .unwrap();
+    assert_eq!(status, "exact_duplicate");
+
+    let (a, b) = (
+        c("30 W 26th St, New York, NY 10010"),
+        c("30 West 26th Street, New York, NY 10010"),
+    );
+    let statuses =
+        take(unsafe { rpostal_compare(handle.0, a.as_ptr(), b.as_ptr(), ptr::null()) }).unwrap();
+    assert_eq!(statuses["house_number"], "exact_duplicate");
+    assert_eq!(statuses["street"], "exact_duplicate");
+}
+
+#[cfg(feature = "format")]
+#[test]
+fn format() {
+    let handle = Handle::new();
+    let components = c(r#"[
+        {"label": "house_number", "value": "30"},
+        {"label": "road", "value": "west 26th street"},
+        {"label": "city", "value": "new york"},
+        {"label": "state", "value": "ny"},
+        {"label": "postcode", "value": "10010"}
+    ]"#);
+    let country = c("US");
+    let label =
+        take(unsafe { rpostal_format(handle.0, components.as_ptr(), country.as_ptr()) }).unwrap();
+    assert_eq!(label, "30 west 26th street\nnew york, ny 10010");
+
+    let country = c("XX");
+    let error = take(unsafe { rpostal_format(handle.0, components.as_ptr(), country.as_ptr()) })
+        .unwrap_err();
+    assert!(error.contains("unknown country"), "{}", error);
+}
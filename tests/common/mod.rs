@@ -0,0 +1,32 @@
+//! Setup shared by the integration tests, which need the libpostal models to be installed.
+
+#![allow(dead_code)]
+
+extern crate once_cell;
+
+use self::once_cell::sync::OnceCell;
+use rpostal::{AddressParser, Core, LanguageClassifier};
+
+static CORE: OnceCell<Core> = OnceCell::new();
+static PARSER: OnceCell<AddressParser<'static>> = OnceCell::new();
+static CLASSIFIER: OnceCell<LanguageClassifier<'static>> = OnceCell::new();
+
+pub fn core() -> &'static Core {
+    CORE.get_or_init(|| Core::setup().expect("failed to setup libpostal"))
+}
+
+pub fn parser() -> &'static AddressParser<'static> {
+    let core = core();
+    PARSER.get_or_init(|| {
+        core.setup_parser()
+            .expect("failed to setup the libpostal address parser")
+    })
+}
+
+pub fn classifier() -> &'static LanguageClassifier<'static> {
+    let core = core();
+    CLASSIFIER.get_or_init(|| {
+        core.setup_language_classifier()
+            .expect("failed to setup the libpostal language classifier")
+    })
+}
@@ -0,0 +1,83 @@
+extern crate rpostal;
+
+mod common;
+
+use rpostal::{KeyOptions, Language, NormalizeOptions};
+
+fn options() -> KeyOptions {
+    KeyOptions {
+        normalize: NormalizeOptions {
+            languages: vec![Language::English],
+            ..NormalizeOptions::default()
+        },
+        ..KeyOptions::default()
+    }
+}
+
+#[test]
+fn union_of_expansions_and_roots() {
+    let core = common::core();
+    let address = "30 W 26th St";
+    let options = options();
+
+    let keys = core.search_keys(address, &options);
+    let expansions = core.expand_address(address, options.normalize.clone());
+    let roots = core.expand_address_root(address, options.normalize.clone());
+    assert!(expansions.iter().all(|e| keys.contains(e)));
+    assert!(roots.iter().all(|e| keys.contains(e)));
+    assert_eq!(
+        keys.len(),
+        expansions
+            .iter()
+            .chain(roots.iter())
+            .collect::<std::collections::BTreeSet<_>>()
+            .len()
+    );
+
+    let only_roots = core.search_keys(
+        address,
+        &KeyOptions {
+            expansions: false,
+            ..options.clone()
+        },
+    );
+    assert!(only_roots.iter().all(|k| roots.contains(k)));
+    let nothing = core.search_keys(
+        address,
+        &KeyOptions {
+            expansions: false,
+            roots: false,
+            ..options
+        },
+    );
+    assert!(nothing.is_empty());
+}
+
+#[test]
+fn keys_are_stable() {
+    let core = common::core();
+    let options = KeyOptions {
+        ngrams: Some((1, 2)),
+        ..options()
+    };
+
+    let keys = core.search_keys("30 W 26th St, New York", &options);
+    assert_eq!(core.search_keys("30 W 26th St, New York", &options), keys);
+    assert!(keys.iter().all(|k| !k.is_empty()));
+}
+
+#[test]
+fn ngrams_are_added() {
+    let core = common::core();
+    let options = KeyOptions {
+        ngrams: Some((1, 2)),
+        ..options()
+    };
+
+    let keys = core.search_keys("30 W 26th St", &options);
+    assert!(keys.contains("30 west 26th street"));
+    assert!(keys.contains("26th"));
+    assert!(keys.contains("west 26th"));
+    // Punctuation and whitespace aren't words.
+    assert!(keys.iter().all(|k| k.trim() == k));
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "tantivy")]
+
+extern crate rpostal;
+extern crate tantivy_tokenizer_api;
+
+use std::sync::Arc;
+
+use rpostal::tantivy::PostalTokenizer;
+use rpostal::{Core, Language, NormalizeOptions};
+use tantivy_tokenizer_api::{TokenStream, Tokenizer};
+
+fn tokens(tokenizer: &mut PostalTokenizer, text: &str) -> Vec<(usize, String, usize, usize)> {
+    let mut stream = tokenizer.token_stream(text);
+    let mut tokens = Vec::new();
+    while stream.advance() {
+        let token = stream.token();
+        tokens.push((
+            token.position,
+            token.text.clone(),
+            token.offset_from,
+            token.offset_to,
+        ));
+    }
+    tokens
+}
+
+fn tokenizer() -> PostalTokenizer {
+    PostalTokenizer::new(Arc::new(Core::setup().expect("failed to setup libpostal")))
+}
+
+#[test]
+fn normalized_tokens() {
+    let tokens = tokens(&mut tokenizer(), "30 W. 26th St");
+
+    assert_eq!(
+        tokens
+            .iter()
+            .map(|t| (t.0, t.1.as_str()))
+            .collect::<Vec<_>>(),
+        [(0, "30"), (1, "w"), (2, "26th"), (3, "st")]
+    );
+    // Offsets are the ones of the input, so "W." is still highlighted whole.
+    assert_eq!((tokens[1].2, tokens[1].3), (3, 5));
+}
+
+#[test]
+fn expansions_are_synonyms() {
+    let mut tokenizer =
+        tokenizer()
+            .with_expansions(true)
+            .with_normalize_options(NormalizeOptions {
+                languages: vec![Language::English],
+                ..NormalizeOptions::default()
+            });
+    let tokens = tokens(&mut tokenizer, "30 W 26th St");
+
+    assert!(tokens.contains(&(1, "west".to_owned(), 3, 4)));
+    assert!(tokens.contains(&(3, "street".to_owned(), 10, 12)));
+    assert!(tokens.windows(2).all(|w| w[0].0 <= w[1].0));
+    assert!(tokens.iter().all(|t| t.0 < 4));
+}